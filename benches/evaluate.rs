@@ -0,0 +1,125 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use circuitry::{Circuit, Error};
+
+// Returns a distinct wire id for each number
+fn name(i: usize) -> String {
+    let mut id = String::new();
+    let mut i = i;
+    loop {
+        id.push((b'a' + (i % 26) as u8) as char);
+        i /= 26;
+        if i == 0 {
+            return id;
+        }
+    }
+}
+
+// Builds a circuit of `n` layers of gates mixing the wires of the previous layers
+fn layered_circuit(n: usize) -> Result<Circuit, Error> {
+    let mut circuit = Circuit::new();
+    circuit.add_wire_with_value(name(0), 1729)?;
+    circuit.add_wire_with_value(name(1), 4936)?;
+    for i in 2..n {
+        match i % 4 {
+            0 => circuit.add_gate_add(name(i), name(i - 1), name(i - 2))?,
+            1 => circuit.add_gate_xor(name(i), name(i - 1), name(i / 2))?,
+            2 => circuit.add_gate_and_value(name(i), name(i - 2), 0x7fff)?,
+            _ => circuit.add_gate_rotl(name(i), name(i - 1), 3)?,
+        }
+    }
+    Ok(circuit)
+}
+
+fn bench_circuit(c: &mut Criterion, name: &str, circuit: Circuit) {
+    let compiled = circuit.compile().unwrap();
+    let inputs = vec![1; compiled.input_ids().len()];
+    let mut signals = compiled.signals_buffer();
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("compute_signals", |b| {
+        b.iter_batched(
+            || circuit.clone(),
+            |mut circuit| circuit.compute_signals().unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| compiled.evaluate_into(&inputs, &mut signals).unwrap())
+    });
+    let assignments: Vec<Vec<u64>> = (0..1024).map(|i| vec![i % 64; inputs.len()]).collect();
+    group.bench_function("compiled_batch_1024", |b| {
+        b.iter(|| compiled.evaluate_batch(&assignments).unwrap())
+    });
+    group.bench_function("compiled_repeated_1024", |b| {
+        b.iter(|| {
+            assignments
+                .iter()
+                .map(|inputs| compiled.evaluate(inputs).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+// Builds a circuit of `depth` levels of `breadth` gates each reading two wires of the level below
+#[cfg(feature = "parallel")]
+fn wide_circuit(depth: usize, breadth: usize) -> Result<Circuit, Error> {
+    let mut circuit = Circuit::new();
+    for i in 0..breadth {
+        circuit.add_wire_with_value(name(i), i as u64)?;
+    }
+    for level in 1..depth {
+        for i in 0..breadth {
+            let id = name(level * breadth + i);
+            let a = name((level - 1) * breadth + i);
+            let b = name((level - 1) * breadth + (i * 7 + 1) % breadth);
+            match i % 3 {
+                0 => circuit.add_gate_add(id, a, b)?,
+                1 => circuit.add_gate_mul(id, a, b)?,
+                _ => circuit.add_gate_xor(id, a, b)?,
+            }
+        }
+    }
+    Ok(circuit)
+}
+
+#[cfg(feature = "parallel")]
+fn bench_parallel(c: &mut Criterion) {
+    let circuit = wide_circuit(100, 1000).unwrap();
+    let mut group = c.benchmark_group("wide_100000");
+    group.sample_size(10);
+    group.bench_function("compute_signals", |b| {
+        b.iter_batched(
+            || circuit.clone(),
+            |mut circuit| circuit.compute_signals().unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("compute_signals_parallel", |b| {
+        b.iter_batched(
+            || circuit.clone(),
+            |mut circuit| circuit.compute_signals_parallel().unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    bench_circuit(
+        c,
+        "nanocorp_2",
+        Circuit::read("circuits/nanocorp_2.txt").unwrap(),
+    );
+    bench_circuit(c, "layered_1000", layered_circuit(1000).unwrap());
+}
+
+criterion_group!(evaluate, benches);
+#[cfg(feature = "parallel")]
+criterion_group!(parallel, bench_parallel);
+
+#[cfg(not(feature = "parallel"))]
+criterion_main!(evaluate);
+#[cfg(feature = "parallel")]
+criterion_main!(evaluate, parallel);
@@ -1,23 +1,50 @@
 use std::{
-    collections::HashMap,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
     fmt::{self, Display, Formatter},
     fs::{self, File},
     io::Write,
     mem,
     path::Path,
+    sync::OnceLock,
 };
 
-use super::wire::{gate::Gate, signal::Signal, wire_id::WireId, wire_input::WireInput, Wire};
-use crate::error::{Error, Result};
+use super::wire::{
+    four_state::FourState, signal::Signal, width::Width, wire_id::WireId, wire_input::WireInput,
+    Wire,
+};
+use crate::{
+    compiled_circuit::CompiledCircuit,
+    error::{Error, Result},
+};
+
+pub mod fixed_point;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod stats;
+pub mod timing;
 
 /// A circuit is a set of connected wires and gates
 ///
 /// A circuit is built by adding wires one at a time.
 /// Each wire has a unique id which is an ascii lowercase string.  
 /// A wire can have three kinds of input:
-/// - a value ([u16])
+/// - a value
 /// - the output of another wire
-/// - a gate combining outputs of other wires
+/// - a gate combining outputs of other wires and constant values
+///
+/// Signals are 16 bits wide unless the circuit is created with
+/// [`with_width()`](Self::with_width), which accepts any width from 1 to 64 bits.
+/// Values, shift amounts and gate outputs all follow that width.
+/// A wire can also declare its own width (see [`set_width_of()`](Self::set_width_of)),
+/// in which case the wires it is connected to must agree with it.
+///
+/// Wires can also be fed unknown (X) or high-impedance (Z) inputs to simulate partially
+/// initialised designs with [`four_state_signals()`](Self::four_state_signals).
+///
+/// A wire can finally be a register (from string "REG d -> q" or "REG d INIT 5 -> q"),
+/// whose signal is the signal its input had at the previous clock cycle
+/// (see [`step()`](Self::step)). Loops are only allowed if they go through a register.
 ///
 /// When first added, a wire's signal is [`Signal::Uncomputed`].
 /// Calling [`compute_signals()`](Self::compute_signals) will compute signals
@@ -81,7 +108,11 @@ use crate::error::{Error, Result};
 /// ```
 #[derive(Clone, Debug, Default)]
 pub struct Circuit {
+    width: Width,
     wires: HashMap<WireId, Wire>,
+    fanout: HashMap<WireId, HashSet<WireId>>,
+    order: OnceLock<Vec<WireId>>,
+    states: HashMap<WireId, Signal>,
     uncomputed: Vec<WireId>,
     uncomputable: Vec<WireId>,
 }
@@ -92,29 +123,101 @@ impl Circuit {
         Self::default()
     }
 
+    /// Creates an empty circuit whose signals are `width` bits wide.  
+    /// Returns an error if `width` is not between 1 and 64.
+    pub fn with_width(width: u8) -> Result<Self> {
+        Ok(Self {
+            width: Width::new(width)?,
+            ..Self::default()
+        })
+    }
+
+    /// Returns the width in bits of the signals of the circuit.
+    pub fn width(&self) -> u8 {
+        self.width.bits()
+    }
+
     fn add(&mut self, wire: Wire) -> Result<()> {
         if self.wires.contains_key(wire.id()) {
             Err(Error::WireIdAlreadyExists(wire.id().to_string()))
         } else {
-            self.uncomputed.push(wire.id().to_owned());
-            self.wires.insert(wire.id().to_owned(), wire);
+            Circuit::check_widths(&self.wires, self.width, &wire, self.readers_of(wire.id()))?;
+            let id = wire.id().to_owned();
+            self.add_to_fanout(&wire);
+            self.uncomputed.push(id.clone());
+            self.wires.insert(id.clone(), wire);
+            // Readers of the new wire may have been uncomputable until now
+            self.invalidate_fanout_of(&id);
             Ok(())
         }
     }
 
+    // Checks that the values and shift amounts of `wire` fit in its width
+    // and that its width is consistent with those of the wires it reads or is read by
+    // (`readers`, taken from `wires`).
+    // `width` is the width of the wires of `wires` with no declared width.
+    pub(super) fn check_widths<'a, I: IntoIterator<Item = &'a Wire>>(
+        wires: &HashMap<WireId, Wire>,
+        width: Width,
+        wire: &Wire,
+        readers: I,
+    ) -> Result<()> {
+        wire.check_width(wire.width_or(width))?;
+        let width_of = |id: &WireId| {
+            if id == wire.id() {
+                Some(wire.width_or(width))
+            } else {
+                wires.get(id).map(|w| w.width_or(width))
+            }
+        };
+        wire.check_input_widths(width, &width_of)?;
+        for reader in readers {
+            reader.check_input_widths(width, &width_of)?;
+        }
+        Ok(())
+    }
+
     /// Adds a wire using string representation.
     /// See [example](Circuit#example-1) for usage.
-
     pub fn add_wire(&mut self, s: &str) -> Result<()> {
-        self.add(Wire::try_from(s)?)
+        self.add(Wire::parse(s)?)
     }
 
     /// Adds a wire `id` whose input is a value.
-    /// Returns an error if `id` is not ascii lowercase.
-    pub fn add_wire_with_value<S: Into<String>>(&mut self, id: S, value: u16) -> Result<()> {
+    /// Returns an error if `id` is not ascii lowercase
+    /// or if `value` does not fit in the width of the circuit.
+    pub fn add_wire_with_value<S: Into<String>>(&mut self, id: S, value: u64) -> Result<()> {
         self.add(Wire::with_value(id, value)?)
     }
 
+    /// Adds a wire `id` whose input is unknown (X).  
+    /// Its signal is only computable by [`four_state_signals()`](Self::four_state_signals).  
+    /// Returns an error if `id` is not ascii lowercase.
+    pub fn add_wire_unknown<S: Into<String>>(&mut self, id: S) -> Result<()> {
+        self.add(Wire::unknown(id)?)
+    }
+
+    /// Adds a wire `id` whose input is high-impedance (Z).  
+    /// Its signal is only computable by [`four_state_signals()`](Self::four_state_signals).  
+    /// Returns an error if `id` is not ascii lowercase.
+    pub fn add_wire_high_impedance<S: Into<String>>(&mut self, id: S) -> Result<()> {
+        self.add(Wire::high_impedance(id)?)
+    }
+
+    /// Adds a register `id` whose signal is `initial` until the first clock cycle
+    /// and then the signal wire `input_id` had at the previous cycle
+    /// (see [`step()`](Self::step)).  
+    /// Returns an error if `id` or `input_id` is not ascii lowercase, if they match
+    /// or if `initial` does not fit in the width of the circuit.
+    pub fn add_register<S: Into<String>, T: Into<String>>(
+        &mut self,
+        id: S,
+        input_id: T,
+        initial: u64,
+    ) -> Result<()> {
+        self.add(Wire::register(id, input_id, initial)?)
+    }
+
     /// Adds a wire `id` whose input is another wire `input_id`.  
     /// Returns an error if `id` or `input_id` is not ascii lowercase
     /// or if `id` and `input_id` match.
@@ -144,7 +247,7 @@ impl Circuit {
         &mut self,
         output: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<()> {
         self.add(Wire::from_gate_and_value(output, input, value)?)
     }
@@ -167,328 +270,1733 @@ impl Circuit {
         &mut self,
         output: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<()> {
         self.add(Wire::from_gate_or_value(output, input, value)?)
     }
 
-    /// Adds a wire `output` fed by a logical LEFT SHIFT gate of wire `input` by amount `shift`.  
+    /// Adds a wire `output` fed by a logical XOR gate between wires `input1` and `input2`.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_xor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_xor(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` fed by a logical XOR gate between wire `input` and value.  
     /// Returns an error if `output` or `input` is not ascii lowercase
     /// or if `output` matches `input`.
-    pub fn add_gate_lshift<S: Into<String>, T: Into<String>>(
+    pub fn add_gate_xor_value<S: Into<String>, T: Into<String>>(
         &mut self,
         output: S,
         input: T,
-        shift: u8,
+        value: u64,
     ) -> Result<()> {
-        self.add(Wire::from_gate_lshift(output, input, shift)?)
+        self.add(Wire::from_gate_xor_value(output, input, value)?)
     }
 
-    /// Adds a wire `output` fed by a logical RIGHT SHIFT gate of wire `input` by amount `shift`.  
+    /// Adds a wire `output` fed by a logical NAND gate between wires `input1` and `input2`.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_nand<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_nand(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` fed by a logical NAND gate between wire `input` and value.  
     /// Returns an error if `output` or `input` is not ascii lowercase
     /// or if `output` matches `input`.
-    pub fn add_gate_rshift<S: Into<String>, T: Into<String>>(
+    pub fn add_gate_nand_value<S: Into<String>, T: Into<String>>(
         &mut self,
         output: S,
         input: T,
-        shift: u8,
+        value: u64,
     ) -> Result<()> {
-        self.add(Wire::from_gate_rshift(output, input, shift)?)
+        self.add(Wire::from_gate_nand_value(output, input, value)?)
     }
 
-    /// Adds a wire `output` fed by a logical NOT gate of wire `input`.  
+    /// Adds a wire `output` fed by a logical NOR gate between wires `input1` and `input2`.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_nor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_nor(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` fed by a logical NOR gate between wire `input` and value.  
     /// Returns an error if `output` or `input` is not ascii lowercase
     /// or if `output` matches `input`.
-    pub fn add_gate_not<S: Into<String>, T: Into<String>>(
+    pub fn add_gate_nor_value<S: Into<String>, T: Into<String>>(
         &mut self,
         output: S,
         input: T,
+        value: u64,
     ) -> Result<()> {
-        self.add(Wire::from_gate_not(output, input)?)
-    }
-
-    pub(super) fn get_wires(&self) -> &HashMap<WireId, Wire> {
-        &self.wires
+        self.add(Wire::from_gate_nor_value(output, input, value)?)
     }
 
-    #[allow(dead_code)]
-    fn get_wire<S: Into<String>>(&self, id: S) -> Result<&Wire> {
-        self.get_wire_of(&WireId::new(id)?)
+    /// Adds a wire `output` fed by a logical XNOR gate between wires `input1` and `input2`.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_xnor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_xnor(output, input1, input2)?)
     }
 
-    fn get_wire_of(&self, id: &WireId) -> Result<&Wire> {
-        self.wires
-            .get(id)
-            .ok_or(Error::UnknownWireId(id.to_string()))
+    /// Adds a wire `output` fed by a logical XNOR gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_xnor_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_xnor_value(output, input, value)?)
     }
 
-    #[allow(dead_code)]
-    fn wire_of(&self, id: &WireId) -> &Wire {
-        self.get_wire_of(id).unwrap()
+    /// Adds a wire `output` fed by an addition gate between wires `input1` and `input2`.  
+    /// Values wrap around modulo 2^width, where width is the output wire's width.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_add<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_add(output, input1, input2)?)
     }
 
-    /// Retrieves signal of wire `id`.  
-    /// If you get the result [`Signal::Uncomputed`], you forgot to call
-    /// [`compute_signals()`](Self::compute_signals).  
-    /// If you get the result [`Signal::Uncomputable`], somewhere up the chain of inputs
-    /// leading to your wire,  
-    /// an input is unknown to the circuit, thus leading to a chain of uncomputable signals.  
-    /// Returns an error if `id` is not ascii lowercase or if circuit has no such wire.
-    pub fn get_signal<S: Into<String>>(&self, id: S) -> Result<Signal> {
-        self.get_signal_of(&WireId::new(id)?)
+    /// Adds a wire `output` fed by an addition gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_add_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_add_value(output, input, value)?)
     }
 
-    /// Infallible version of the previous function.
-    pub fn signal<S: Into<String>>(&self, id: S) -> Signal {
-        self.get_signal(id).unwrap()
+    /// Adds a wire `output` fed by a subtraction gate between wires `input1` and `input2`.  
+    /// Values wrap around modulo 2^width, where width is the output wire's width.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_sub<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_sub(output, input1, input2)?)
     }
 
-    fn get_signal_of(&self, id: &WireId) -> Result<Signal> {
-        self.get_wire_of(id).map(|w| *w.signal())
+    /// Adds a wire `output` fed by a subtraction gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_sub_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_sub_value(output, input, value)?)
     }
 
-    #[allow(dead_code)]
-    fn signal_of(&self, id: &WireId) -> Signal {
-        self.get_signal_of(id).unwrap()
+    /// Adds a wire `output` fed by a multiplication gate between wires `input1` and `input2`.  
+    /// Values wrap around modulo 2^width, where width is the output wire's width.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_mul<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_mul(output, input1, input2)?)
     }
 
-    fn set_signal_of(&mut self, id: &WireId, signal: Signal) -> Result<()> {
-        self.wires
-            .get_mut(id)
-            .ok_or(Error::UnknownWireId(id.to_string()))
-            .map(|w| {
-                w.set_signal(signal);
-            })
+    /// Adds a wire `output` fed by a multiplication gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_mul_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_mul_value(output, input, value)?)
     }
 
-    /// Computes signals of all wires in the circuit.  
-    /// If you add wires after calling this function, you need to call it again to compute
-    /// the signals of the new wires  
-    /// (and potentially previously uncomputable signals).  
-    /// Returns error if the circuit has a loop.
-    pub fn compute_signals(&mut self) -> Result<()> {
-        let mut to_be_computed = mem::take(&mut self.uncomputable);
-        for id in &mut to_be_computed {
-            self.set_signal_of(id, Signal::Uncomputed).unwrap();
-        }
-        to_be_computed.append(&mut self.uncomputed);
-        self.compute_signals_of(to_be_computed)?;
-        self.uncomputable.sort();
-        self.uncomputable.dedup();
-        Ok(())
+    /// Adds a wire `output` fed by an integer division gate between wires `input1` and `input2`.  
+    /// A zero divisor makes the signal of `output` [`Signal::Uncomputable`].  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_div<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_div(output, input1, input2)?)
     }
 
-    /// Computes the signal of wire `id`.  
-    /// Returns an error if `id` is not ascii lowercase or if the circuit has no such wire.
-    pub fn compute_signal<S: Into<String>>(&mut self, id: S) -> Result<Signal> {
-        let id = WireId::new(id)?;
-        self.compute_signals_of(vec![id.clone()])?;
-        self.get_signal_of(&id)
+    /// Adds a wire `output` fed by an integer division gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`
+    /// or if `value` is zero.
+    pub fn add_gate_div_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_div_value(output, input, value)?)
     }
 
-    // Computes the signals of all the wires from ids
-    //
-    // (Note: For clarity, wires are identified with their ids in this comment)
-    //
-    // Proceeds by iteration starting from the end of the vector until it is empty
-    // 1) Select the last id of the vector and mark it as root if there is none
-    // 2a) If that id has an uncomputable signal or does not exist,
-    //     mark all the ids from root until the end of the vector as uncomputable,
-    //     pop them from the vector and go back to 1)
-    // 2b) If that id has a signal, pop it from the vector and go back to 1)
-    // 3) Otherwise that id has an uncomputed signal. Look for its inputs
-    // 3a) If an input has an uncomputed signal:
-    // 3a1) If that input is already in [root..], the circuit has a loop. Error.
-    // 3a2) Otherwise add it to the vector and go back to 1)
-    // 3b) If an input has an uncomputable signal or does not exist,
-    //     mark all the ids from root until the end of the vector as uncomputable,
-    //     pop them from the vector and go back to 1) (same as 2a)
-    // 3c) Otherwise all inputs have signals.
-    //     Deduce the signal of id, pop it from the vector and go back to 1)
-    fn compute_signals_of(&mut self, mut ids: Vec<WireId>) -> Result<()> {
-        let mut root_index = if ids.is_empty() { 0 } else { ids.len() - 1 };
-        while let Some(id) = ids.last() {
-            if root_index > ids.len() - 1 {
-                root_index = ids.len() - 1;
-            }
-            if let Some(wire) = self.wires.get(id) {
-                match wire.signal() {
-                    Signal::Value(_) => {
-                        ids.pop();
-                    }
-                    Signal::Uncomputable => {
-                        ids = self.set_uncomputable_from_index(ids, root_index);
-                    }
-                    Signal::Uncomputed => match wire.input() {
-                        WireInput::Value(value) => {
-                            self.set_signal_of(id, Signal::Value(*value)).unwrap();
-                            ids.pop();
-                        }
-                        WireInput::Wire(input_id) => {
-                            if let Ok(input_wire) = self.get_wire_of(input_id) {
-                                match input_wire.signal() {
-                                    Signal::Value(signal) => {
-                                        self.set_signal_of(id, Signal::Value(*signal)).unwrap();
-                                        ids.pop();
-                                    }
-                                    Signal::Uncomputable => {
-                                        ids = self.set_uncomputable_from_index(ids, root_index);
-                                    }
-                                    Signal::Uncomputed => {
-                                        if ids[root_index..].contains(input_id) {
-                                            return Err(Error::CircuitLoop);
-                                        }
-                                        ids.push(input_id.to_owned());
-                                    }
-                                }
-                            } else {
-                                ids = self.set_uncomputable_from_index(ids, root_index);
-                            }
-                        }
-                        WireInput::Gate(gate) => match gate {
-                            Gate::And { input1, input2 } | Gate::Or { input1, input2 } => {
-                                if let (Ok(wire1), Ok(wire2)) =
-                                    (self.get_wire_of(input1), self.get_wire_of(input2))
-                                {
-                                    match (wire1.signal(), wire2.signal()) {
-                                        (Signal::Value(signal1), Signal::Value(signal2)) => {
-                                            self.set_signal_of(
-                                                id,
-                                                gate.signal(*signal1, Some(*signal2)),
-                                            )
-                                            .unwrap();
-                                            ids.pop();
-                                        }
-                                        (Signal::Uncomputable, _) | (_, Signal::Uncomputable) => {
-                                            ids = self.set_uncomputable_from_index(ids, root_index);
-                                        }
-                                        (Signal::Uncomputed, _) => {
-                                            if ids[root_index..].contains(input1) {
-                                                return Err(Error::CircuitLoop);
-                                            }
-                                            ids.push(input1.to_owned());
-                                        }
-                                        (_, Signal::Uncomputed) => {
-                                            if ids[root_index..].contains(input2) {
-                                                return Err(Error::CircuitLoop);
-                                            }
-                                            ids.push(input2.to_owned());
-                                        }
-                                    }
-                                } else {
-                                    ids = self.set_uncomputable_from_index(ids, root_index);
-                                }
-                            }
-                            Gate::AndValue { input, .. }
-                            | Gate::OrValue { input, .. }
-                            | Gate::LShift { input, .. }
-                            | Gate::RShift { input, .. }
-                            | Gate::Not { input } => {
-                                if let Ok(input_wire) = self.get_wire_of(input) {
-                                    match input_wire.signal() {
-                                        Signal::Value(signal) => {
-                                            self.set_signal_of(id, gate.signal(*signal, None))
-                                                .unwrap();
-                                            ids.pop();
-                                        }
-                                        Signal::Uncomputable => {
-                                            ids = self.set_uncomputable_from_index(ids, root_index);
-                                        }
-                                        Signal::Uncomputed => {
-                                            if ids[root_index..].contains(input) {
-                                                return Err(Error::CircuitLoop);
-                                            }
-                                            ids.push(input.to_owned());
-                                        }
-                                    }
-                                } else {
-                                    ids = self.set_uncomputable_from_index(ids, root_index);
-                                }
-                            }
-                        },
-                    },
-                }
-            } else {
-                ids = self.set_uncomputable_from_index(ids, root_index);
-            }
-        }
-        Ok(())
+    /// Adds a wire `output` fed by a modulo gate between wires `input1` and `input2`.  
+    /// A zero divisor makes the signal of `output` [`Signal::Uncomputable`].  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_mod<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_mod(output, input1, input2)?)
     }
 
-    // Helper function of compute_signals_of()
-    fn set_uncomputable_from_index(
+    /// Adds a wire `output` fed by a modulo gate between wire `input` and value.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`
+    /// or if `value` is zero.
+    pub fn add_gate_mod_value<S: Into<String>, T: Into<String>>(
         &mut self,
-        mut ids: Vec<WireId>,
-        root_index: usize,
-    ) -> Vec<WireId> {
-        for id in &ids[root_index..] {
-            self.set_signal_of(id, Signal::Uncomputable).unwrap();
-            self.uncomputable.push(id.to_owned());
-        }
-        ids.truncate(root_index);
-        ids
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_mod_value(output, input, value)?)
     }
 
-    /// Prints all signals.  
-    /// The implementation of [`Circuit`] uses a [`HashMap`](std::collections::HashMap).
-    /// For that reason, the ordering is random.
-    pub fn print_signals(&self) {
-        for wire in self.wires.values() {
-            println!("{}: {:?}", wire.id(), wire.signal());
-        }
+    /// Adds a wire `output` emitting 1 if wire `input1` is equal to wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_eq<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_eq(output, input1, input2)?)
     }
 
-    /// Reads circuit from a file assuming a wire per line.  
-    /// See [example](Circuit#example-1) for how to represent a wire with a string
-    /// or use the next function to get clues!
-    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let s = fs::read_to_string(path)?;
-        Self::try_from(s.as_str())
+    /// Adds a wire `output` emitting 1 if wire `input` is equal to value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_eq_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_eq_value(output, input, value)?)
     }
 
-    /// Writes circuit to a file.
-    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let data = self.to_string();
-        let mut f = File::create(path)?;
-        Ok(f.write_all(data.as_bytes())?)
+    /// Adds a wire `output` emitting 1 if wire `input1` is not equal to wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_ne<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_ne(output, input1, input2)?)
     }
 
-    /// Remove wire `id` from circuit then reset all signals (to [`Signal::Uncomputed`]).  
-    /// Returns an error if `id` is not ascii lowercase or if circuit has not such wire.
-    /// If an error occurs, signals are not reset.
-    pub fn remove_wire_then_reset_signals<S: Into<String>>(&mut self, id: S) -> Result<()> {
-        let id = WireId::new(id)?;
-        self.wires
-            .remove(&id)
-            .ok_or(Error::UnknownWireId(id.to_string()))
-            .map(|_| {
-                self.reset_signals();
-            })
+    /// Adds a wire `output` emitting 1 if wire `input` is not equal to value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_ne_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_ne_value(output, input, value)?)
     }
 
-    #[allow(dead_code)]
-    fn set_wire_then_reset_signals(&mut self, wire: Wire) -> Result<()> {
-        if let Some(w) = self.wires.get_mut(wire.id()) {
-            *w = wire;
-            self.reset_signals();
-            Ok(())
-        } else {
-            Err(Error::UnknownWireId(wire.id().to_string()))
-        }
+    /// Adds a wire `output` emitting 1 if wire `input1` is less than wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_lt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_lt(output, input1, input2)?)
     }
 
-    fn reset_signals(&mut self) {
+    /// Adds a wire `output` emitting 1 if wire `input` is less than value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_lt_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_lt_value(output, input, value)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input1` is less than or equal to wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_le<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_le(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input` is less than or equal to value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_le_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_le_value(output, input, value)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input1` is greater than wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_gt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_gt(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input` is greater than value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_gt_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_gt_value(output, input, value)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input1` is greater than or equal to wire `input2` and 0 otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_ge<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_ge(output, input1, input2)?)
+    }
+
+    /// Adds a wire `output` emitting 1 if wire `input` is greater than or equal to value and 0 otherwise.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_ge_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_ge_value(output, input, value)?)
+    }
+
+    /// Adds a wire `output` fed by a logical LEFT SHIFT gate of wire `input` by amount `shift`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_lshift<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_lshift(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a logical RIGHT SHIFT gate of wire `input` by amount `shift`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_rshift<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rshift(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a logical LEFT SHIFT gate of wire `input` by the signal of wire `shift`.  
+    /// A shift amount not less than the wire width shifts all bits out.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_lshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_lshift_wire(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a logical RIGHT SHIFT gate of wire `input` by the signal of wire `shift`.  
+    /// A shift amount not less than the wire width shifts all bits out.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_rshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rshift_wire(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a LEFT ROTATE gate of wire `input` by amount `shift`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_rotl<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rotl(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a LEFT ROTATE gate of wire `input` by the signal of wire `shift`.  
+    /// The rotation amount is taken modulo the wire width.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_rotl_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rotl_wire(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a RIGHT ROTATE gate of wire `input` by amount `shift`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_rotr<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rotr(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a RIGHT ROTATE gate of wire `input` by the signal of wire `shift`.  
+    /// The rotation amount is taken modulo the wire width.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_rotr_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_rotr_wire(output, input, shift)?)
+    }
+
+    /// Adds a wire `output` fed by a logical NOT gate of wire `input`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase
+    /// or if `output` matches `input`.
+    pub fn add_gate_not<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_not(output, input)?)
+    }
+
+    /// Adds a wire `output` fed by a multiplexer selecting wire `input1`
+    /// if the signal of wire `select` is nonzero and wire `input2` otherwise.  
+    /// Returns an error if any id is not ascii lowercase or if `output` matches an input.
+    pub fn add_gate_mux<S: Into<String>, T: Into<String>, U: Into<String>, V: Into<String>>(
+        &mut self,
+        output: S,
+        select: T,
+        input1: U,
+        input2: V,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_mux(output, select, input1, input2)?)
+    }
+
+    /// Adds a wire `output` emitting bits `msb` down to `lsb` of wire `input`.  
+    /// Returns an error if `output` or `input` is not ascii lowercase,
+    /// if `output` matches `input`, if `lsb` exceeds `msb` or if `msb` exceeds 63.
+    pub fn add_gate_slice<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        msb: u8,
+        lsb: u8,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_slice(output, input, msb, lsb)?)
+    }
+
+    /// Adds a wire `output` emitting the concatenation of the signals of wires `inputs`,
+    /// the first input providing the most significant bits.  
+    /// Returns an error if any id is not ascii lowercase, if `output` matches an input
+    /// or if there is no input.
+    pub fn add_gate_concat<S: Into<String>, I: IntoIterator<Item = T>, T: Into<String>>(
+        &mut self,
+        output: S,
+        inputs: I,
+    ) -> Result<()> {
+        self.add(Wire::from_gate_concat(output, inputs)?)
+    }
+
+    /// Returns the width in bits of the signal of wire `id`,
+    /// which is the width of the circuit unless declared otherwise.  
+    /// Returns an error if `id` is not ascii lowercase or if circuit has no such wire.
+    pub fn width_of<S: Into<String>>(&self, id: S) -> Result<u8> {
+        let id = WireId::new(id)?;
+        Ok(self.get_wire_of(&id)?.width_or(self.width).bits())
+    }
+
+    /// Declares the signal of wire `id` to be `width` bits wide
+    /// then reset all signals (to [`Signal::Uncomputed`]).  
+    /// A width can also be declared with the string representation of a wire,
+    /// writing either `x:8` or `[7:0] x` as output.  
+    /// Returns an error if `id` is not ascii lowercase, if circuit has no such wire,
+    /// if `width` is not between 1 and 64, if the input of the wire does not fit in `width`
+    /// or if `width` does not match the width of a wire connected to `id`.
+    /// If an error occurs, signals are not reset.
+    pub fn set_width_of<S: Into<String>>(&mut self, id: S, width: u8) -> Result<()> {
+        let id = WireId::new(id)?;
+        let mut wire = self.get_wire_of(&id)?.clone();
+        wire.set_width(Some(Width::new(width)?));
+        Circuit::check_widths(&self.wires, self.width, &wire, self.readers_of(wire.id()))?;
+        self.set_wire_then_invalidate_fanout(wire)
+    }
+
+    // Returns the wires which read wire `id`
+    fn readers_of<'a>(&'a self, id: &WireId) -> impl Iterator<Item = &'a Wire> + 'a {
+        self.fanout
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|reader| self.wires.get(reader))
+    }
+
+    pub(super) fn get_width(&self) -> Width {
+        self.width
+    }
+
+    pub(super) fn get_wires(&self) -> &HashMap<WireId, Wire> {
+        &self.wires
+    }
+
+    fn get_wire<S: Into<String>>(&self, id: S) -> Result<&Wire> {
+        self.get_wire_of(&WireId::new(id)?)
+    }
+
+    fn get_wire_of(&self, id: &WireId) -> Result<&Wire> {
+        self.wires
+            .get(id)
+            .ok_or(Error::UnknownWireId(id.to_string()))
+    }
+
+    #[allow(dead_code)]
+    fn wire_of(&self, id: &WireId) -> &Wire {
+        self.get_wire_of(id).unwrap()
+    }
+
+    /// Retrieves signal of wire `id`.  
+    /// If you get the result [`Signal::Uncomputed`], you forgot to call
+    /// [`compute_signals()`](Self::compute_signals).  
+    /// If you get the result [`Signal::Uncomputable`], somewhere up the chain of inputs
+    /// leading to your wire,  
+    /// an input is unknown to the circuit, thus leading to a chain of uncomputable signals.  
+    /// Returns an error if `id` is not ascii lowercase or if circuit has no such wire.
+    pub fn get_signal<S: Into<String>>(&self, id: S) -> Result<Signal> {
+        self.get_signal_of(&WireId::new(id)?)
+    }
+
+    /// Infallible version of the previous function.
+    pub fn signal<S: Into<String>>(&self, id: S) -> Signal {
+        self.get_signal(id).unwrap()
+    }
+
+    fn get_signal_of(&self, id: &WireId) -> Result<Signal> {
+        self.get_wire_of(id).map(|w| *w.signal())
+    }
+
+    #[allow(dead_code)]
+    fn signal_of(&self, id: &WireId) -> Signal {
+        self.get_signal_of(id).unwrap()
+    }
+
+    fn set_signal_of(&mut self, id: &WireId, signal: Signal) -> Result<()> {
+        self.wires
+            .get_mut(id)
+            .ok_or(Error::UnknownWireId(id.to_string()))
+            .map(|w| {
+                w.set_signal(signal);
+            })
+    }
+
+    /// Computes signals of all wires in the circuit.  
+    /// If you add or change wires after calling this function, you need to call it again
+    /// to compute the signals of the new wires  
+    /// (and potentially previously uncomputable signals).  
+    /// Only the signals of the wires added or changed since the last call
+    /// and of the wires they transitively feed are recomputed,
+    /// in a single pass following the [evaluation order](Self::evaluation_order).  
+    /// Returns error if the circuit has a loop.
+    pub fn compute_signals(&mut self) -> Result<()> {
+        if !self.uncomputed.is_empty() {
+            self.order()?;
+            let order = mem::take(&mut self.order);
+            self.compute_signals_of(order.get().unwrap());
+            self.order = order;
+            self.uncomputed.clear();
+        }
+        Ok(())
+    }
+
+    /// Computes the signal of wire `id`
+    /// (and of the wires it transitively reads).  
+    /// Returns an error if `id` is not ascii lowercase, if the circuit has no such wire
+    /// or if the wires `id` transitively reads have a loop
+    /// (loops elsewhere in the circuit do not matter).
+    pub fn compute_signal<S: Into<String>>(&mut self, id: S) -> Result<Signal> {
+        let id = WireId::new(id)?;
+        let (id, _) = self
+            .wires
+            .get_key_value(&id)
+            .ok_or(Error::UnknownWireId(id.to_string()))?;
+        let mut fanin = self.reachable_from(id, |id| {
+            self.wires
+                .get(id)
+                .map_or(vec![], Wire::combinational_inputs)
+        });
+        fanin.retain(|id| self.wires.contains_key(*id));
+        fanin.insert(id);
+        let order = self.topological_order_of(&fanin)?;
+        let id = id.to_owned();
+        self.compute_signals_of(&order);
+        self.get_signal_of(&id)
+    }
+
+    /// Returns the ids of all wires in an order where each wire comes after its inputs,
+    /// ties being broken by alphabetical order.  
+    /// This is the order in which [`compute_signals()`](Self::compute_signals)
+    /// evaluates wires. It is computed with Kahn's algorithm after each change
+    /// to the structure of the circuit and cached until the next one.  
+    /// Returns an error if the circuit has a loop.
+    pub fn evaluation_order(&self) -> Result<Vec<String>> {
+        Ok(self.order()?.iter().map(WireId::to_string).collect())
+    }
+
+    /// Compiles the circuit into a [`CompiledCircuit`] for fast repeated evaluation
+    /// with different input values.  
+    /// The compiled circuit is a snapshot: later changes to the circuit do not affect it.  
+    /// Returns an error if the circuit has a loop.
+    pub fn compile(&self) -> Result<CompiledCircuit> {
+        CompiledCircuit::new(self)
+    }
+
+    // Returns the cached evaluation order, sorting the wires first if needed
+    pub(super) fn order(&self) -> Result<&Vec<WireId>> {
+        if let Some(order) = self.order.get() {
+            return Ok(order);
+        }
+        let order = self.topological_order()?;
+        Ok(self.order.get_or_init(|| order))
+    }
+
+    // Sorts the wires with Kahn's algorithm: wires whose inputs are all sorted
+    // (or not in the circuit) are ready, and the smallest ready id comes next.
+    // Registers do not wait for their inputs, their signals being those latched
+    // at the previous clock cycle
+    fn topological_order(&self) -> Result<Vec<WireId>> {
+        self.topological_order_of(&self.wires.keys().collect())
+    }
+
+    // Sorts the wires of `ids` like topological_order(), the wires they read
+    // which are not in `ids` being considered sorted
+    fn topological_order_of(&self, ids: &HashSet<&WireId>) -> Result<Vec<WireId>> {
+        let mut in_degrees: HashMap<&WireId, usize> = ids
+            .iter()
+            .map(|id| {
+                let inputs: HashSet<&WireId> = self
+                    .wire_of(id)
+                    .combinational_inputs()
+                    .into_iter()
+                    .filter(|input| ids.contains(*input))
+                    .collect();
+                (*id, inputs.len())
+            })
+            .collect();
+        let mut ready: BinaryHeap<Reverse<&WireId>> = in_degrees
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(id, _)| Reverse(*id))
+            .collect();
+        let mut order = Vec::with_capacity(ids.len());
+        while let Some(Reverse(id)) = ready.pop() {
+            order.push(id.to_owned());
+            let readers = self.fanout.get(id).into_iter().flatten();
+            for reader in readers
+                .filter(|reader| ids.contains(*reader) && !self.wire_of(reader).is_register())
+            {
+                let degree = in_degrees.get_mut(reader).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(Reverse(reader));
+                }
+            }
+        }
+        if order.len() == ids.len() {
+            Ok(order)
+        } else {
+            let remaining = in_degrees
+                .into_iter()
+                .filter(|(_, degree)| *degree > 0)
+                .map(|(id, _)| id)
+                .collect();
+            Err(Error::CircuitLoop(
+                self.find_cycle(&remaining)
+                    .iter()
+                    .map(WireId::to_string)
+                    .collect(),
+            ))
+        }
+    }
+
+    // Returns a loop among the wires of `remaining`, each of which reads one of the others,
+    // in the order signals flow and starting from its smallest id.
+    // The loop is found by following the smallest remaining input of each wire from the
+    // smallest remaining wire, until a wire comes up a second time
+    fn find_cycle(&self, remaining: &HashSet<&WireId>) -> Vec<WireId> {
+        let first = *remaining.iter().min().unwrap();
+        let mut path = vec![first];
+        let mut positions = HashMap::from([(first, 0)]);
+        loop {
+            let input = self
+                .wire_of(path.last().unwrap())
+                .combinational_inputs()
+                .into_iter()
+                .filter(|input| remaining.contains(input))
+                .min()
+                .unwrap();
+            if let Some(start) = positions.get(input) {
+                let mut cycle: Vec<WireId> = path[*start..]
+                    .iter()
+                    .rev()
+                    .map(|id| (*id).to_owned())
+                    .collect();
+                let smallest = (0..cycle.len()).min_by_key(|i| &cycle[*i]).unwrap();
+                cycle.rotate_left(smallest);
+                return cycle;
+            }
+            positions.insert(input, path.len());
+            path.push(input);
+        }
+    }
+
+    /// Returns the strongly connected components of the circuit which have loops,
+    /// without evaluating anything.
+    /// Each component is a set of wires any two of which transitively read each other.  
+    /// Ids are sorted within each component and components are sorted by their ids.
+    /// The circuit has no loop if the result is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::new()
+    ///     .add_wire("1 -> a")?
+    ///     .add_wire("a AND c -> b")?
+    ///     .add_wire("NOT b -> c")?
+    ///     .add_wire("c -> d")?
+    ///     .add_wire("d OR e -> f")?
+    ///     .add_wire("f -> e")?
+    ///     .build();
+    ///
+    /// assert_eq!(circuit.find_cycles(), [vec!["b", "c"], vec!["e", "f"]]);
+    /// assert_eq!(
+    ///     circuit.compute_signals().unwrap_err().to_string(),
+    ///     "Circuit has a loop: b -> c -> b"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        self.loops()
+            .into_iter()
+            .map(|component| component.into_iter().map(WireId::to_string).collect())
+            .collect()
+    }
+
+    /// Checks the structure of the circuit and returns all the problems found
+    /// in an [`Error::InvalidCircuit`], in this order:
+    /// - wires which are read but not driven ([`Error::UndrivenWire`])
+    /// - loops, one per [strongly connected component](Self::find_cycles)
+    ///   ([`Error::CircuitLoop`])
+    /// - wires which neither read nor are read by any other wire ([`Error::UnusedWire`])
+    /// - wires whose values or shift amounts do not fit in their widths
+    ///   ([`Error::WidthViolation`]) or whose widths do not match those of their inputs
+    ///   ([`Error::WidthMismatch`])
+    ///
+    /// Wires are sorted by id within each kind of problem.
+    /// Nothing is evaluated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let circuit = CircuitBuilder::new()
+    ///     .add_wire("1 -> a")?
+    ///     .add_wire("2 -> b")?
+    ///     .add_wire("a AND c -> d")?
+    ///     .add_wire("d OR e -> f")?
+    ///     .add_wire("NOT f -> e")?
+    ///     .build();
+    ///
+    /// match circuit.validate() {
+    ///     Err(Error::InvalidCircuit(problems)) => assert_eq!(
+    ///         problems.iter().map(Error::to_string).collect::<Vec<_>>(),
+    ///         [
+    ///             "Wire 'c' is read but not driven",
+    ///             "Circuit has a loop: e -> f -> e",
+    ///             "Wire 'b' is not connected to any other wire",
+    ///         ]
+    ///     ),
+    ///     _ => unreachable!(),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let mut problems: Vec<Error> = self
+            .undriven_wires()
+            .into_iter()
+            .map(Error::UndrivenWire)
+            .collect();
+
+        for component in self.loops() {
+            let cycle = self.find_cycle(&component.into_iter().collect());
+            problems.push(Error::CircuitLoop(
+                cycle.iter().map(WireId::to_string).collect(),
+            ));
+        }
+
+        let mut ids: Vec<&WireId> = self.wires.keys().collect();
+        ids.sort();
+        for id in &ids {
+            if self.is_unused(self.wire_of(id)) {
+                problems.push(Error::UnusedWire(id.to_string()));
+            }
+        }
+
+        let width_of = |id: &WireId| self.wires.get(id).map(|w| w.width_or(self.width));
+        for id in &ids {
+            let wire = self.wire_of(id);
+            if let Err(error) = wire.check_width(wire.width_or(self.width)) {
+                problems.push(Error::WidthViolation(id.to_string(), Box::new(error)));
+            }
+            if let Err(error) = wire.check_input_widths(self.width, &width_of) {
+                problems.push(error);
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidCircuit(problems))
+        }
+    }
+
+    // Returns the strongly connected components of the circuit which have loops,
+    // each sorted, in order
+    fn loops(&self) -> Vec<Vec<&WireId>> {
+        // Tarjan's algorithm, with an explicit stack of the wires being visited
+        // and of the position of the next input to visit for each of them
+        let mut ids: Vec<&WireId> = self.wires.keys().collect();
+        ids.sort();
+        let inputs_of = |id: &WireId| -> Vec<&WireId> {
+            let mut inputs: Vec<&WireId> = self
+                .wire_of(id)
+                .combinational_inputs()
+                .into_iter()
+                .filter(|input| self.wires.contains_key(*input))
+                .collect();
+            inputs.sort();
+            inputs.dedup();
+            inputs
+        };
+        let mut indices: HashMap<&WireId, usize> = HashMap::new();
+        let mut low_links: HashMap<&WireId, usize> = HashMap::new();
+        let mut stack: Vec<&WireId> = Vec::new();
+        let mut on_stack: HashSet<&WireId> = HashSet::new();
+        let mut components: Vec<Vec<&WireId>> = Vec::new();
+        for root in ids {
+            if indices.contains_key(root) {
+                continue;
+            }
+            let mut visits = vec![(root, inputs_of(root), 0)];
+            indices.insert(root, indices.len());
+            low_links.insert(root, indices[root]);
+            stack.push(root);
+            on_stack.insert(root);
+            while let Some((id, inputs, next)) = visits.last_mut() {
+                let id = *id;
+                if let Some(input) = inputs.get(*next).copied() {
+                    *next += 1;
+                    if !indices.contains_key(input) {
+                        visits.push((input, inputs_of(input), 0));
+                        indices.insert(input, indices.len());
+                        low_links.insert(input, indices[input]);
+                        stack.push(input);
+                        on_stack.insert(input);
+                    } else if on_stack.contains(input) {
+                        let low_link = low_links[id].min(indices[input]);
+                        low_links.insert(id, low_link);
+                    }
+                    continue;
+                }
+                visits.pop();
+                if let Some((parent, _, _)) = visits.last() {
+                    let low_link = low_links[parent].min(low_links[id]);
+                    low_links.insert(parent, low_link);
+                }
+                if low_links[id] == indices[id] {
+                    let mut component = Vec::new();
+                    loop {
+                        let member = stack.pop().unwrap();
+                        on_stack.remove(member);
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 {
+                        component.sort();
+                        components.push(component);
+                    }
+                }
+            }
+        }
+        components.sort();
+        components
+    }
+
+    // Computes the uncomputed signals of the wires of `order`, in which the inputs
+    // of a wire always come before it
+    fn compute_signals_of(&mut self, order: &[WireId]) {
+        for id in order {
+            let wire = self.wire_of(id);
+            if *wire.signal() == Signal::Uncomputed {
+                let signal = self.evaluate(wire);
+                if signal == Signal::Uncomputable {
+                    self.uncomputable.push(id.to_owned());
+                }
+                self.set_signal_of(id, signal).unwrap();
+            }
+        }
+        self.uncomputable.sort();
+        self.uncomputable.dedup();
+    }
+
+    // Computes the signal of `wire` from the signals of its inputs.
+    // A wire is uncomputable if it reads a wire which is not in the circuit
+    // or whose signal is uncomputable (or uncomputed)
+    fn evaluate(&self, wire: &Wire) -> Signal {
+        self.evaluate_from(wire, |id| {
+            self.get_signal_of(id).unwrap_or(Signal::Uncomputable)
+        })
+    }
+
+    // Computes the signal of `wire` from the signals of its inputs given by `signal_of`
+    fn evaluate_from<F: Fn(&WireId) -> Signal>(&self, wire: &Wire, signal_of: F) -> Signal {
+        match wire.input() {
+            WireInput::Value(value) => Signal::Value(*value),
+            WireInput::Unknown | WireInput::HighImpedance => Signal::Uncomputable,
+            WireInput::Register { .. } => self.state_of(wire),
+            WireInput::Wire(input_id) => match signal_of(input_id) {
+                Signal::Value(signal) => Signal::Value(signal),
+                _ => Signal::Uncomputable,
+            },
+            WireInput::Gate(gate) => {
+                let mut signals = Vec::with_capacity(3);
+                let mut widths = Vec::with_capacity(3);
+                for input in gate.inputs() {
+                    match (signal_of(input), self.wires.get(input)) {
+                        (Signal::Value(signal), Some(input_wire)) => {
+                            signals.push(signal);
+                            widths.push(input_wire.width_or(self.width));
+                        }
+                        _ => return Signal::Uncomputable,
+                    }
+                }
+                gate.signal(&signals, &widths, wire.width_or(self.width))
+            }
+        }
+    }
+
+    // Returns the signal of register `wire`: its initial value until the first clock cycle
+    fn state_of(&self, wire: &Wire) -> Signal {
+        match (self.states.get(wire.id()), wire.input()) {
+            (Some(state), _) => *state,
+            (None, WireInput::Register { initial, .. }) => Signal::Value(*initial),
+            (None, _) => unreachable!(),
+        }
+    }
+
+    /// Advances the circuit by one clock cycle: each register latches the signal
+    /// of its input, then the signals of the wires they transitively feed are recomputed.
+    /// A register whose input is uncomputable (or not in the circuit) becomes uncomputable.  
+    /// Returns an error if the circuit has a loop which does not go through a register.
+    ///
+    /// # Example
+    ///
+    /// A 2-bit counter:
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Signal, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::with_width(2)?
+    ///     .add_wire("REG next -> count")?
+    ///     .add_wire("count ADD 1 -> next")?
+    ///     .build();
+    ///
+    /// circuit.compute_signals()?;
+    /// assert_eq!(circuit.signal("count"), Signal::Value(0));
+    /// circuit.step()?;
+    /// circuit.step()?;
+    /// circuit.step()?;
+    /// assert_eq!(circuit.signal("count"), Signal::Value(3));
+    /// circuit.step()?;
+    /// assert_eq!(circuit.signal("count"), Signal::Value(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn step(&mut self) -> Result<()> {
+        self.compute_signals()?;
+        let latched: Vec<(WireId, Signal)> = self
+            .wires
+            .values()
+            .filter_map(|wire| match wire.input() {
+                WireInput::Register { input, .. } => {
+                    let signal = match self.get_signal_of(input) {
+                        Ok(Signal::Value(value)) => Signal::Value(value),
+                        _ => Signal::Uncomputable,
+                    };
+                    Some((wire.id().to_owned(), signal))
+                }
+                _ => None,
+            })
+            .collect();
+        for (id, state) in latched {
+            if state != self.state_of(self.wire_of(&id)) {
+                self.states.insert(id.clone(), state);
+                self.invalidate_fanout_of(&id);
+            }
+        }
+        self.compute_signals()
+    }
+
+    /// Runs the circuit for `cycles` clock cycles and returns the signals of all wires
+    /// at each cycle by wire id, starting with the current one.
+    /// The circuit is then [`step()`](Self::step)ped `cycles` times.  
+    /// Returns an error if the circuit has a loop which does not go through a register.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Signal, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::new()
+    ///     .add_wire("REG b INIT 1 -> a")?
+    ///     .add_wire("REG a -> b")?
+    ///     .build();
+    ///
+    /// let traces = circuit.run(3)?;
+    /// let a: Vec<Signal> = traces.iter().map(|signals| signals["a"]).collect();
+    /// assert_eq!(a, [Signal::Value(1), Signal::Value(0), Signal::Value(1)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run(&mut self, cycles: usize) -> Result<Vec<HashMap<String, Signal>>> {
+        let mut traces = Vec::with_capacity(cycles);
+        self.compute_signals()?;
+        for _ in 0..cycles {
+            traces.push(
+                self.wires
+                    .iter()
+                    .map(|(id, wire)| (id.to_string(), *wire.signal()))
+                    .collect(),
+            );
+            self.step()?;
+        }
+        Ok(traces)
+    }
+
+    /// Resets all registers to their initial values.  
+    /// The signals of the wires they transitively feed then need recomputing.
+    pub fn reset_registers(&mut self) {
+        for id in mem::take(&mut self.states).into_keys() {
+            self.invalidate_fanout_of(&id);
+        }
+    }
+
+    /// Computes the signals of all wires in four-state logic, where each bit is
+    /// 0, 1, unknown (X) or high-impedance (Z), and returns them by wire id.  
+    /// Unlike [`compute_signals()`](Self::compute_signals), an unknown input does not make
+    /// the signals it feeds uncomputable: wires which are referenced but not in the circuit
+    /// are unknown (as are wires added with [`add_wire_unknown()`](Self::add_wire_unknown)
+    /// or from string "X -> id") and gates only output unknown bits where their inputs
+    /// cannot determine them (see [`FourState`]).
+    /// High-impedance inputs (from string "Z -> id") are passed along by wires
+    /// but are treated as unknown by gates.  
+    /// The signals stored in the circuit are left untouched.  
+    /// Returns an error if the circuit has a loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let circuit = CircuitBuilder::with_width(4)?
+    ///     .add_wire("3 -> a")?
+    ///     .add_wire("a AND x -> b")?    // Wire x is not in the circuit
+    ///     .add_wire("b AND 4 -> c")?
+    ///     .build();
+    ///
+    /// let signals = circuit.four_state_signals()?;
+    /// assert_eq!(signals["b"].to_string(), "00xx");
+    /// assert_eq!(signals["c"].value(), Some(0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn four_state_signals(&self) -> Result<HashMap<String, FourState>> {
+        let mut signals: HashMap<&WireId, FourState> = HashMap::new();
+        for id in self.order()? {
+            let wire = self.wire_of(id);
+            let width = wire.width_or(self.width);
+            let input_signal = |input: &WireId| match signals.get(input) {
+                Some(signal) => *signal,
+                None => FourState::unknown(width),
+            };
+            let signal = match wire.input() {
+                WireInput::Value(value) => FourState::known(*value, width),
+                WireInput::Unknown => FourState::unknown(width),
+                WireInput::HighImpedance => FourState::high_impedance(width),
+                WireInput::Register { .. } => match self.state_of(wire) {
+                    Signal::Value(value) => FourState::known(value, width),
+                    _ => FourState::unknown(width),
+                },
+                WireInput::Wire(input) => input_signal(input).resize(width),
+                WireInput::Gate(gate) => {
+                    let inputs: Vec<FourState> =
+                        gate.inputs().into_iter().map(input_signal).collect();
+                    gate.four_state_signal(&inputs, width)
+                }
+            };
+            signals.insert(id, signal);
+        }
+        Ok(signals
+            .into_iter()
+            .map(|(id, signal)| (id.to_string(), signal))
+            .collect())
+    }
+
+    /// Returns the ids of the wires read by wire `id`, in the order of its operands.  
+    /// Returns an error if `id` is not ascii lowercase or if the circuit has no such wire.
+    pub fn inputs_of<S: Into<String>>(&self, id: S) -> Result<Vec<String>> {
+        let mut inputs: Vec<String> = Vec::new();
+        for input in self.get_wire(id)?.inputs() {
+            let input = input.to_string();
+            if !inputs.contains(&input) {
+                inputs.push(input);
+            }
+        }
+        Ok(inputs)
+    }
+
+    /// Returns the ids of the wires reading wire `id`, sorted.
+    /// `id` can be an [undriven wire](Self::undriven_wires).  
+    /// Returns an error if `id` is not ascii lowercase or if the circuit neither has
+    /// nor reads such a wire.
+    pub fn fanout_of<S: Into<String>>(&self, id: S) -> Result<Vec<String>> {
+        let id = self.known_id(&WireId::new(id)?)?;
+        Ok(Circuit::sorted(self.fanout.get(id).into_iter().flatten()))
+    }
+
+    /// Returns the ids of the wires wire `id` transitively reads (its cone of influence),
+    /// undriven wires included, sorted.  
+    /// Returns an error if `id` is not ascii lowercase or if the circuit neither has
+    /// nor reads such a wire.
+    pub fn transitive_fanin<S: Into<String>>(&self, id: S) -> Result<Vec<String>> {
+        let id = self.known_id(&WireId::new(id)?)?;
+        let mut fanin =
+            self.reachable_from(id, |id| self.wires.get(id).map_or(vec![], Wire::inputs));
+        fanin.remove(id);
+        Ok(Circuit::sorted(fanin))
+    }
+
+    /// Returns the ids of the wires transitively reading wire `id`, sorted.  
+    /// Returns an error if `id` is not ascii lowercase or if the circuit neither has
+    /// nor reads such a wire.
+    pub fn transitive_fanout<S: Into<String>>(&self, id: S) -> Result<Vec<String>> {
+        let id = self.known_id(&WireId::new(id)?)?;
+        let mut fanout =
+            self.reachable_from(id, |id| self.fanout.get(id).into_iter().flatten().collect());
+        fanout.remove(id);
+        Ok(Circuit::sorted(fanout))
+    }
+
+    /// Returns an iterator over the ids of the primary inputs of the circuit,
+    /// the wires whose input is a value, in random order.
+    pub fn primary_inputs(&self) -> impl Iterator<Item = &str> + '_ {
         self.wires
-            .values_mut()
-            .for_each(|w| w.set_signal(Signal::Uncomputed));
-        self.uncomputable = vec![];
-        self.uncomputed = self.wires.keys().cloned().collect();
+            .values()
+            .filter(|wire| matches!(wire.input(), WireInput::Value(_)))
+            .map(|wire| wire.id().as_str())
+    }
+
+    /// Returns an iterator over the ids of the primary outputs of the circuit,
+    /// the wires which no wire reads, in random order.
+    pub fn primary_outputs(&self) -> impl Iterator<Item = &str> + '_ {
+        self.wires
+            .keys()
+            .filter(|id| !self.fanout.contains_key(*id))
+            .map(WireId::as_str)
+    }
+
+    // Returns true if the wire neither reads nor is read by any other wire
+    fn is_unused(&self, wire: &Wire) -> bool {
+        wire.inputs().is_empty() && !self.fanout.contains_key(wire.id())
+    }
+
+    // Returns the id of the circuit equal to `id`, which is either a wire of the circuit
+    // or a wire it reads
+    fn known_id(&self, id: &WireId) -> Result<&WireId> {
+        self.wires
+            .get_key_value(id)
+            .map(|(id, _)| id)
+            .or_else(|| self.fanout.get_key_value(id).map(|(id, _)| id))
+            .ok_or(Error::UnknownWireId(id.to_string()))
+    }
+
+    // Returns the wires reached from wire `id` by repeatedly following `next`
+    fn reachable_from<'a, F>(&'a self, id: &'a WireId, next: F) -> HashSet<&'a WireId>
+    where
+        F: Fn(&'a WireId) -> Vec<&'a WireId>,
+    {
+        let mut reached = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            for next_id in next(id) {
+                if reached.insert(next_id) {
+                    stack.push(next_id);
+                }
+            }
+        }
+        reached
+    }
+
+    // Returns the ids sorted, as strings
+    fn sorted<'a>(ids: impl IntoIterator<Item = &'a WireId>) -> Vec<String> {
+        let mut ids: Vec<String> = ids.into_iter().map(WireId::to_string).collect();
+        ids.sort();
+        ids
+    }
+
+    /// Returns the ids of the wires which are read by wires of the circuit
+    /// but are not in it, sorted.
+    /// Their readers are uncomputable.
+    pub fn undriven_wires(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .fanout
+            .keys()
+            .filter(|id| !self.wires.contains_key(*id))
+            .map(WireId::to_string)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Explains the signal of wire `id`, and in particular why it is
+    /// [`Signal::Uncomputable`], as a chain of diagnostics from the wire
+    /// to the root cause: an undriven wire (read but not in the circuit),
+    /// an unknown (X) or high-impedance (Z) input or a division by zero.  
+    /// `id` can also be an undriven wire.  
+    /// Returns an error if `id` is not ascii lowercase or if the circuit neither has
+    /// nor reads such a wire.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::new()
+    ///     .add_wire("1 -> a")?
+    ///     .add_wire("a AND b -> lw")?
+    ///     .add_wire("lw OR b -> lx")?
+    ///     .build();
+    /// circuit.compute_signals()?;
+    ///
+    /// assert_eq!(circuit.undriven_wires(), ["b"]);
+    /// assert_eq!(
+    ///     circuit.explain("lx")?,
+    ///     [
+    ///         "lx is uncomputable because it reads lw",
+    ///         "lw is uncomputable because it reads b",
+    ///         "b is undriven (referenced by lw, lx)",
+    ///     ]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn explain<S: Into<String>>(&self, id: S) -> Result<Vec<String>> {
+        let mut id = WireId::new(id)?;
+        let mut chain: Vec<String> = Vec::new();
+        let mut visited = HashSet::new();
+        loop {
+            let wire = match self.wires.get(&id) {
+                Some(wire) => wire,
+                None => {
+                    let mut readers: Vec<String> = self
+                        .fanout
+                        .get(&id)
+                        .ok_or(Error::UnknownWireId(id.to_string()))?
+                        .iter()
+                        .map(WireId::to_string)
+                        .collect();
+                    readers.sort();
+                    chain.push(format!(
+                        "{} is undriven (referenced by {})",
+                        id,
+                        readers.join(", ")
+                    ));
+                    return Ok(chain);
+                }
+            };
+            visited.insert(id.clone());
+            let cause = match (wire.signal(), wire.input()) {
+                (Signal::Value(value), _) => format!("{} is computable (value {})", id, value),
+                (Signal::Uncomputed, _) => format!("{} is not computed yet", id),
+                (Signal::Uncomputable, WireInput::Unknown) => format!("{} is unknown (X)", id),
+                (Signal::Uncomputable, WireInput::HighImpedance) => {
+                    format!("{} is high-impedance (Z)", id)
+                }
+                (Signal::Uncomputable, input) => {
+                    // Follow the first input which is undriven or uncomputable
+                    let culprit = wire.inputs().into_iter().find(|input| {
+                        !visited.contains(*input)
+                            && self
+                                .get_signal_of(input)
+                                .map_or(true, |signal| signal == Signal::Uncomputable)
+                    });
+                    match (culprit, input) {
+                        (Some(culprit), WireInput::Register { .. }) => {
+                            chain.push(format!(
+                                "{} latched an uncomputable signal from {}",
+                                id, culprit
+                            ));
+                            id = culprit.to_owned();
+                            continue;
+                        }
+                        (Some(culprit), _) => {
+                            chain.push(format!(
+                                "{} is uncomputable because it reads {}",
+                                id, culprit
+                            ));
+                            id = culprit.to_owned();
+                            continue;
+                        }
+                        (None, WireInput::Register { input, .. }) => {
+                            format!("{} latched an uncomputable signal from {}", id, input)
+                        }
+                        (None, WireInput::Gate(gate)) if gate.divides() => {
+                            format!("{} is uncomputable because {} divides by zero", id, gate)
+                        }
+                        (None, _) => format!("{} is uncomputable", id),
+                    }
+                }
+            };
+            chain.push(cause);
+            return Ok(chain);
+        }
+    }
+
+    /// Prints all signals.  
+    /// The implementation of [`Circuit`] uses a [`HashMap`](std::collections::HashMap).
+    /// For that reason, the ordering is random.
+    pub fn print_signals(&self) {
+        for wire in self.wires.values() {
+            println!("{}: {:?}", wire.id(), wire.signal());
+        }
+    }
+
+    /// Reads circuit from a file assuming a wire per line.  
+    /// The file may start with a line `WIDTH n` declaring that signals are `n` bits wide,
+    /// as [`write()`](Self::write) does for circuits whose width is not 16.  
+    /// See [example](Circuit#example-1) for how to represent a wire with a string
+    /// or use the next function to get clues!
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let s = fs::read_to_string(path)?;
+        Self::try_from(s.as_str())
+    }
+
+    /// Reads circuit from a file like [`read()`](Self::read),
+    /// for signals `width` bits wide unless the file declares another width.
+    pub fn read_with_width<P: AsRef<Path>>(path: P, width: u8) -> Result<Self> {
+        let s = fs::read_to_string(path)?;
+        Self::parse(&s, Width::new(width)?)
+    }
+
+    // Builds a circuit of signals `width` bits wide (unless a first line `WIDTH n`
+    // declares another width) from a wire per line
+    fn parse(s: &str, width: Width) -> Result<Self> {
+        let mut lines = s.trim_end().split('\n').peekable();
+        let width = match lines.peek().and_then(|line| line.strip_prefix("WIDTH ")) {
+            Some(bits) => {
+                let bits = bits
+                    .parse()
+                    .map_err(|_| Error::ParseWidth(bits.to_string()))?;
+                lines.next();
+                Width::new(bits)?
+            }
+            None => width,
+        };
+        let mut circuit = Circuit {
+            width,
+            ..Circuit::default()
+        };
+        for wire in lines {
+            circuit.add_wire(wire)?
+        }
+        Ok(circuit)
+    }
+
+    /// Writes circuit to a file, a wire per line, after a line `WIDTH n`
+    /// if signals are not 16 bits wide (see [`read()`](Self::read)).
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let data = self.to_string();
+        let mut f = File::create(path)?;
+        Ok(f.write_all(data.as_bytes())?)
+    }
+
+    /// Writes the current signals of wires `ids` to a Value Change Dump (VCD) file,
+    /// to be viewed in a waveform viewer such as GTKWave.
+    /// See [`write_vcd_traces()`](Self::write_vcd_traces) for the format.  
+    /// Returns an error if an id is not ascii lowercase, if the circuit has no such wire
+    /// or if the file cannot be written.
+    pub fn write_vcd<P: AsRef<Path>, S: AsRef<str>>(&self, path: P, ids: &[S]) -> Result<()> {
+        let signals: HashMap<String, Signal> = self
+            .wires
+            .iter()
+            .map(|(id, wire)| (id.to_string(), *wire.signal()))
+            .collect();
+        self.write_vcd_traces(path, ids, &[signals])
+    }
+
+    /// Writes the signals of wires `ids` over several evaluations to a Value Change Dump
+    /// (VCD) file, to be viewed in a waveform viewer such as GTKWave.
+    /// `traces` holds the signals of the wires at each time step by wire id,
+    /// as returned by [`run()`](Self::run).  
+    /// Each wire is a variable of its width in bits. Its value is dumped at time 0,
+    /// then at each time step where it changes.
+    /// [`Signal::Uncomputed`] and [`Signal::Uncomputable`] signals, as well as signals
+    /// missing from a trace, are dumped as 'x'.  
+    /// Returns an error if an id is not ascii lowercase, if the circuit has no such wire
+    /// or if the file cannot be written.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::with_width(4)?
+    ///     .add_wire("REG next -> count")?
+    ///     .add_wire("count ADD 1 -> next")?
+    ///     .build();
+    ///
+    /// let traces = circuit.run(16)?;
+    /// circuit.write_vcd_traces("counter.vcd", &["count", "next"], &traces)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_vcd_traces<P: AsRef<Path>, S: AsRef<str>>(
+        &self,
+        path: P,
+        ids: &[S],
+        traces: &[HashMap<String, Signal>],
+    ) -> Result<()> {
+        let data = self.vcd(ids, traces)?;
+        let mut f = File::create(path)?;
+        Ok(f.write_all(data.as_bytes())?)
+    }
+
+    // Returns the VCD of the signals of wires `ids` along `traces`.
+    // Variables are identified by codes made of the printable ascii characters
+    fn vcd<S: AsRef<str>>(&self, ids: &[S], traces: &[HashMap<String, Signal>]) -> Result<String> {
+        let code = |mut i: usize| {
+            let mut code = String::new();
+            loop {
+                code.push(char::from(b'!' + (i % 94) as u8));
+                i /= 94;
+                if i == 0 {
+                    return code;
+                }
+            }
+        };
+        let mut variables = Vec::with_capacity(ids.len());
+        for (i, id) in ids.iter().enumerate() {
+            let id = WireId::new(id.as_ref())?;
+            let width = self.get_wire_of(&id)?.width_or(self.width).bits();
+            variables.push((id.to_string(), code(i), width));
+        }
+
+        let mut vcd = format!(
+            "$version circuitry {} $end\n$timescale 1ns $end\n$scope module circuit $end\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        for (id, code, width) in &variables {
+            vcd.push_str(&format!("$var wire {} {} {} $end\n", width, code, id));
+        }
+        vcd.push_str("$upscope $end\n$enddefinitions $end\n");
+
+        let mut previous: Vec<Option<Option<u64>>> = vec![None; variables.len()];
+        for (time, signals) in traces.iter().enumerate() {
+            let mut changes = String::new();
+            for ((id, code, width), previous) in variables.iter().zip(&mut previous) {
+                let value = match signals.get(id) {
+                    Some(Signal::Value(value)) => Some(*value),
+                    _ => None,
+                };
+                if *previous == Some(value) {
+                    continue;
+                }
+                *previous = Some(value);
+                let change = match (value, width) {
+                    (Some(value), 1) => format!("{}{}\n", value, code),
+                    (None, 1) => format!("x{}\n", code),
+                    (Some(value), _) => format!("b{:b} {}\n", value, code),
+                    (None, _) => format!("bx {}\n", code),
+                };
+                changes.push_str(&change);
+            }
+            if time == 0 {
+                vcd.push_str(&format!("#0\n$dumpvars\n{}$end\n", changes));
+            } else if !changes.is_empty() {
+                vcd.push_str(&format!("#{}\n{}", time, changes));
+            }
+        }
+        Ok(vcd)
+    }
+
+    /// Remove wire `id` from circuit then reset the signals of the wires it transitively fed
+    /// (to [`Signal::Uncomputed`]).  
+    /// Returns an error if `id` is not ascii lowercase or if circuit has not such wire.
+    /// If an error occurs, signals are not reset.
+    pub fn remove_wire_then_reset_signals<S: Into<String>>(&mut self, id: S) -> Result<()> {
+        let id = WireId::new(id)?;
+        let wire = self
+            .wires
+            .remove(&id)
+            .ok_or(Error::UnknownWireId(id.to_string()))?;
+        self.remove_from_fanout(&wire);
+        self.states.remove(&id);
+        self.uncomputed.retain(|uncomputed| uncomputed != &id);
+        self.uncomputable.retain(|uncomputable| uncomputable != &id);
+        self.invalidate_fanout_of(&id);
+        Ok(())
+    }
+
+    /// Replaces the input of wire `id` with `value`, keeping its declared width if any.  
+    /// The signals of `id` and of the wires it transitively feeds are then uncomputed
+    /// until the next call to [`compute_signals()`](Self::compute_signals),
+    /// which leaves the other signals untouched.  
+    /// Returns an error if `id` is not ascii lowercase, if the circuit has no such wire
+    /// or if `value` does not fit in the width of the wire.
+    ///
+    /// # Example
+    ///
+    /// Overriding wire b with the signal of wire a then recomputing:
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Signal, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut circuit = CircuitBuilder::new()
+    ///     .add_wire("3 -> b")?
+    ///     .add_wire("b LSHIFT 2 -> a")?
+    ///     .build();
+    /// circuit.compute_signals()?;
+    ///
+    /// if let Signal::Value(a) = circuit.signal("a") {
+    ///     circuit.set_value("b", a)?;
+    /// }
+    /// circuit.compute_signals()?;
+    /// assert_eq!(circuit.signal("a"), Signal::Value(48));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_value<S: Into<String>>(&mut self, id: S, value: u64) -> Result<()> {
+        self.override_input(Wire::with_value(id, value)?)
+    }
+
+    /// Rewires wire `id` to take its input from wire `input_id`,
+    /// keeping its declared width if any.  
+    /// See [`set_value()`](Self::set_value) for the signals needing recomputation.  
+    /// Returns an error if `id` or `input_id` is not ascii lowercase, if they match,
+    /// if the circuit has no wire `id` or if the widths of both wires differ.
+    pub fn set_input_from_wire<S: Into<String>, T: Into<String>>(
+        &mut self,
+        id: S,
+        input_id: T,
+    ) -> Result<()> {
+        self.override_input(Wire::from_wire(id, input_id)?)
+    }
+
+    /// Replaces the input of wire `id` with `input`, the string representation
+    /// of a value, a wire or a gate (e.g. "42", "a" or "a AND b"),
+    /// keeping the declared width of `id` if any.  
+    /// See [`set_value()`](Self::set_value) for the signals needing recomputation.  
+    /// Returns an error if `input` cannot be parsed, if the circuit has no wire `id`,
+    /// if `input` reads `id` or if the widths of `id` and of its new inputs disagree.
+    pub fn set_input<S: Into<String>>(&mut self, id: S, input: &str) -> Result<()> {
+        let id = WireId::new(id)?;
+        self.override_input(Wire::parse(&format!("{} -> {}", input, id))?)
+    }
+
+    // Replaces the wire of the circuit with the id of `wire`,
+    // `wire` inheriting the declared width of the replaced wire
+    fn override_input(&mut self, mut wire: Wire) -> Result<()> {
+        wire.set_width(self.get_wire_of(wire.id())?.width());
+        self.replace(wire)
+    }
+
+    /// Replaces the wire with the same id as the wire represented by string `s`
+    /// (see [example](Circuit#example-1)).  
+    /// As with [`set_value()`](Self::set_value), only the signals of that wire
+    /// and of the wires it transitively feeds need recomputing.  
+    /// Returns an error if `s` cannot be parsed, if the circuit has no wire with that id
+    /// or if the widths of the new wire and of the wires it is connected to disagree.
+    pub fn replace_wire(&mut self, s: &str) -> Result<()> {
+        self.replace(Wire::parse(s)?)
+    }
+
+    // Replaces the wire of the circuit with the id of `wire` after checking its widths
+    fn replace(&mut self, wire: Wire) -> Result<()> {
+        self.get_wire_of(wire.id())?;
+        Circuit::check_widths(&self.wires, self.width, &wire, self.readers_of(wire.id()))?;
+        self.set_wire_then_invalidate_fanout(wire)
+    }
+
+    fn set_wire_then_invalidate_fanout(&mut self, wire: Wire) -> Result<()> {
+        let id = wire.id().to_owned();
+        let old_wire = self
+            .wires
+            .get(&id)
+            .ok_or(Error::UnknownWireId(id.to_string()))?
+            .clone();
+        self.remove_from_fanout(&old_wire);
+        self.add_to_fanout(&wire);
+        self.wires.insert(id.clone(), wire);
+        self.states.remove(&id);
+        self.uncomputed.push(id.clone());
+        self.invalidate_fanout_of(&id);
+        Ok(())
+    }
+
+    // Records `wire` as a reader of each of its inputs
+    // (the evaluation order needs sorting again)
+    fn add_to_fanout(&mut self, wire: &Wire) {
+        self.order.take();
+        for input in wire.inputs() {
+            self.fanout
+                .entry(input.to_owned())
+                .or_default()
+                .insert(wire.id().to_owned());
+        }
+    }
+
+    // Forgets `wire` as a reader of its inputs
+    // (the evaluation order needs sorting again)
+    fn remove_from_fanout(&mut self, wire: &Wire) {
+        self.order.take();
+        for input in wire.inputs() {
+            if let Some(readers) = self.fanout.get_mut(input) {
+                readers.remove(wire.id());
+                if readers.is_empty() {
+                    self.fanout.remove(input);
+                }
+            }
+        }
+    }
+
+    // Marks the signals of wire `id` and of all the wires it transitively feeds
+    // as uncomputed so that the next call to compute_signals() recomputes them
+    fn invalidate_fanout_of(&mut self, id: &WireId) {
+        let mut stack = vec![id.to_owned()];
+        let mut visited = HashSet::new();
+        while let Some(id) = stack.pop() {
+            if visited.contains(&id) {
+                continue;
+            }
+            if let Some(readers) = self.fanout.get(&id) {
+                stack.extend(readers.iter().filter(|r| !visited.contains(*r)).cloned());
+            }
+            visited.insert(id);
+        }
+        self.uncomputable.retain(|id| !visited.contains(id));
+        for id in visited {
+            if let Some(wire) = self.wires.get_mut(&id) {
+                if *wire.signal() != Signal::Uncomputed {
+                    wire.set_signal(Signal::Uncomputed);
+                    self.uncomputed.push(id);
+                }
+            }
+        }
+    }
+
+    pub(super) fn set_width(&mut self, width: Width) {
+        self.width = width;
     }
 
     pub(super) fn set_wires(&mut self, wires: HashMap<WireId, Wire>) {
+        self.fanout = HashMap::new();
+        for wire in wires.values() {
+            self.add_to_fanout(wire);
+        }
         self.wires = wires;
     }
 
+    pub(super) fn into_wires(self) -> HashMap<WireId, Wire> {
+        self.wires
+    }
+
     pub(super) fn set_uncomputed(&mut self, uncomputed: Vec<WireId>) {
         self.uncomputed = uncomputed;
     }
@@ -496,13 +2004,14 @@ impl Circuit {
     // Tests if both circuits have the same wires (ids, inputs and signals)
     #[allow(dead_code)]
     pub(super) fn equals(&self, other: &Self) -> bool {
-        if self.wires.len() != other.wires.len() {
+        if self.width != other.width || self.wires.len() != other.wires.len() {
             return false;
         }
         for (id1, wire1) in &self.wires {
             if let Ok(wire2) = other.get_wire_of(id1) {
                 if wire1.id() != wire2.id()
                     || wire1.input() != wire2.input()
+                    || wire1.width() != wire2.width()
                     || wire1.signal() != wire2.signal()
                 {
                     return false;
@@ -520,16 +2029,15 @@ impl TryFrom<&str> for Circuit {
     type Error = Error;
 
     fn try_from(s: &str) -> Result<Self> {
-        let mut circuit = Circuit::new();
-        for wire in s.trim_end().split('\n') {
-            circuit.add(wire.try_into()?)?
-        }
-        Ok(circuit)
+        Self::parse(s, Width::default())
     }
 }
 
 impl Display for Circuit {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.width != Width::default() {
+            writeln!(f, "WIDTH {}", self.width.bits())?
+        }
         for wire in self.wires.values() {
             writeln!(f, "{}", wire)?
         }
@@ -540,6 +2048,7 @@ impl Display for Circuit {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::CircuitBuilder;
 
     #[test]
     fn empty_circuit() -> Result<()> {
@@ -653,6 +2162,111 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn vcd() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(4)?
+            .add_wire("REG next -> count")?
+            .add_wire("count ADD 1 -> next")?
+            .add_wire("count[3] -> top:1")?
+            .add_wire("next AND missing -> never")?
+            .build();
+        let traces = c.run(10)?;
+        let vcd = c.vcd(&["count", "top", "never"], &traces[7..])?;
+        assert_eq!(
+            vcd.split_once("$scope").unwrap().1,
+            " module circuit $end
+$var wire 4 ! count $end
+$var wire 1 \" top $end
+$var wire 4 # never $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+b111 !
+0\"
+bx #
+$end
+#1
+b1000 !
+1\"
+#2
+b1001 !
+"
+        );
+
+        c.write_vcd("circuits/counter.vcd", &["count"])?;
+        let vcd = fs::read_to_string("circuits/counter.vcd")?;
+        fs::remove_file("circuits/counter.vcd")?;
+        assert!(vcd.ends_with("#0\n$dumpvars\nb1010 !\n$end\n"));
+
+        assert!(matches!(
+            c.vcd(&["count", "missing"], &traces),
+            Err(Error::UnknownWireId(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn explain() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(8)?
+            .add_wire("0 -> zero")?
+            .add_wire("5 -> five")?
+            .add_wire("five DIV zero -> q")?
+            .add_wire("q ADD five -> r")?
+            .add_wire("X -> x")?
+            .add_wire("NOT x -> nx")?
+            .add_wire("Z -> z")?
+            .add_wire("z -> zz")?
+            .add_wire("REG r -> state")?
+            .add_wire("five AND missing -> m")?
+            .add_wire("m OR other -> n")?
+            .build();
+        assert_eq!(c.explain("r")?, ["r is not computed yet"]);
+        c.step()?;
+        assert_eq!(c.undriven_wires(), ["missing", "other"]);
+
+        assert_eq!(c.explain("five")?, ["five is computable (value 5)"]);
+        assert_eq!(
+            c.explain("r")?,
+            [
+                "r is uncomputable because it reads q",
+                "q is uncomputable because five DIV zero divides by zero"
+            ]
+        );
+        assert_eq!(
+            c.explain("nx")?,
+            ["nx is uncomputable because it reads x", "x is unknown (X)"]
+        );
+        assert_eq!(
+            c.explain("zz")?,
+            [
+                "zz is uncomputable because it reads z",
+                "z is high-impedance (Z)"
+            ]
+        );
+        assert_eq!(
+            c.explain("n")?,
+            [
+                "n is uncomputable because it reads m",
+                "m is uncomputable because it reads missing",
+                "missing is undriven (referenced by m)"
+            ]
+        );
+        assert_eq!(c.explain("other")?, ["other is undriven (referenced by n)"]);
+        assert!(matches!(c.explain("nothing"), Err(Error::UnknownWireId(_))));
+
+        c.set_value("zero", 1)?;
+        c.compute_signals()?;
+        assert_eq!(
+            c.explain("state")?,
+            ["state latched an uncomputable signal from r"]
+        );
+        c.add_wire("0 -> missing")?;
+        c.add_wire("1 -> other")?;
+        assert!(c.undriven_wires().is_empty());
+        Ok(())
+    }
+
     #[test]
     fn read_write_read_nanocorp_2() -> Result<()> {
         let c1 = Circuit::read("circuits/nanocorp_2.txt")?;
@@ -705,6 +2319,681 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn derived_gates() -> Result<()> {
+        let (x, y) = (0x5a3c, 0x0ff0);
+        let mut c = Circuit::try_from(
+            "23100 -> x\n\
+             4080 -> y\n\
+             x XOR y -> xor\n\
+             x NAND y -> nand\n\
+             x NOR y -> nor\n\
+             x XNOR y -> xnor\n\
+             x XOR 4080 -> xorv\n\
+             4080 NAND x -> nandv\n\
+             x NOR 4080 -> norv\n\
+             x XNOR 4080 -> xnorv",
+        )?;
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("xor"), Signal::Value(x ^ y));
+        assert_eq!(c.signal("nand"), Signal::Value(!(x & y) & 0xffff));
+        assert_eq!(c.signal("nor"), Signal::Value(!(x | y) & 0xffff));
+        assert_eq!(c.signal("xnor"), Signal::Value(!(x ^ y) & 0xffff));
+        assert_eq!(c.signal("xorv"), Signal::Value(x ^ y));
+        assert_eq!(c.signal("nandv"), Signal::Value(!(x & y) & 0xffff));
+        assert_eq!(c.signal("norv"), Signal::Value(!(x | y) & 0xffff));
+        assert_eq!(c.signal("xnorv"), Signal::Value(!(x ^ y) & 0xffff));
+        Ok(())
+    }
+
+    #[test]
+    fn arithmetic_gates() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire_with_value("x", 0xfff0)?
+            .add_wire_with_value("y", 0x0020)?
+            .add_wire_with_value("zero", 0)?
+            .add_gate_add("sum", "x", "y")?
+            .add_gate_sub("diff", "y", "x")?
+            .add_gate_mul("prod", "x", "y")?
+            .add_gate_div("quot", "x", "y")?
+            .add_gate_mod("rem", "x", "y")?
+            .add_gate_add_value("inc", "x", 0x10)?
+            .add_gate_sub_value("dec", "zero", 1)?
+            .add_gate_div("divz", "x", "zero")?
+            .add_gate_mod("modz", "x", "zero")?
+            .add_gate_not("ndivz", "divz")?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("sum"), Signal::Value(0x0010));
+        assert_eq!(c.signal("diff"), Signal::Value(0x0030));
+        assert_eq!(c.signal("prod"), Signal::Value(0xfe00));
+        assert_eq!(c.signal("quot"), Signal::Value(0x07ff));
+        assert_eq!(c.signal("rem"), Signal::Value(0x0010));
+        assert_eq!(c.signal("inc"), Signal::Value(0));
+        assert_eq!(c.signal("dec"), Signal::Value(0xffff));
+        assert_eq!(c.signal("divz"), Signal::Uncomputable);
+        assert_eq!(c.signal("modz"), Signal::Uncomputable);
+        assert_eq!(c.signal("ndivz"), Signal::Uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn shift_by_wire() -> Result<()> {
+        let mut c = Circuit::try_from(
+            "42350 -> x\n\
+             4 -> four\n\
+             20 -> twenty\n\
+             x LSHIFT four -> l\n\
+             x RSHIFT twenty -> r\n\
+             x ROTL twenty -> rl\n\
+             x ROTR 4 -> rr\n\
+             x ROTL unknown -> u",
+        )?;
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("l"), Signal::Value(0x56e0));
+        assert_eq!(c.signal("r"), Signal::Value(0));
+        assert_eq!(c.signal("rl"), Signal::Value(0x56ea));
+        assert_eq!(c.signal("rr"), Signal::Value(0xea56));
+        assert_eq!(c.signal("u"), Signal::Uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn multiplexer() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("1 -> on")?
+            .add_wire("0 -> off")?
+            .add_wire("123 -> a")?
+            .add_wire("456 -> b")?
+            .add_wire("on MUX a b -> x")?
+            .add_wire("off MUX a b -> y")?
+            .add_gate_mux("z", "a", "x", "unknown")?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("x"), Signal::Value(123));
+        assert_eq!(c.signal("y"), Signal::Value(456));
+        assert_eq!(c.signal("z"), Signal::Uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn loop_mux() -> Result<()> {
+        let mut c = Circuit::new();
+        c.add_wire_with_value("s", 0)?;
+        c.add_wire_with_value("a", 1)?;
+        c.add_gate_mux("b", "s", "a", "c")?;
+        c.add_gate_not("c", "b")?;
+        assert!(matches!(c.compute_signals(), Err(Error::CircuitLoop(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn loop_report() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("1 -> a")?
+            .add_wire("a AND z -> y")?
+            .add_wire("y OR x -> w")?
+            .add_wire("NOT w -> z")?
+            .add_wire("w -> x")?
+            .add_wire("y -> b")?
+            .build();
+        match c.compute_signals() {
+            Err(Error::CircuitLoop(cycle)) => assert_eq!(cycle, ["w", "x"]),
+            _ => panic!("loop not detected"),
+        }
+        assert_eq!(
+            c.evaluation_order().unwrap_err().to_string(),
+            "Circuit has a loop: w -> x -> w"
+        );
+        assert_eq!(c.find_cycles(), [vec!["w", "x", "y", "z"]]);
+
+        c.set_input("x", "a")?;
+        match c.compute_signals() {
+            Err(Error::CircuitLoop(cycle)) => assert_eq!(cycle, ["w", "z", "y"]),
+            _ => panic!("loop not detected"),
+        }
+        assert_eq!(c.find_cycles(), [vec!["w", "y", "z"]]);
+
+        c.set_input("z", "a")?;
+        assert!(c.find_cycles().is_empty());
+        c.compute_signals()?;
+        Ok(())
+    }
+
+    #[test]
+    fn registers() -> Result<()> {
+        // Serial-in shift register with a parity bit
+        let mut c = CircuitBuilder::with_width(1)?
+            .add_wire("1 -> input")?
+            .add_wire("REG input -> a")?
+            .add_wire("REG a INIT 1 -> b")?
+            .add_register("cc", "b", 0)?
+            .add_wire("a XOR b -> ab")?
+            .add_wire("ab XOR cc -> parity")?
+            .build();
+        assert!(c.find_cycles().is_empty());
+        c.compute_signals()?;
+        assert_eq!(c.signal("b"), Signal::Value(1));
+        assert_eq!(c.signal("parity"), Signal::Value(1));
+
+        let traces = c.run(4)?;
+        let bits = |id: &str| -> Vec<Signal> { traces.iter().map(|t| t[id]).collect() };
+        use Signal::Value;
+        assert_eq!(bits("a"), [Value(0), Value(1), Value(1), Value(1)]);
+        assert_eq!(bits("b"), [Value(1), Value(0), Value(1), Value(1)]);
+        assert_eq!(bits("cc"), [Value(0), Value(1), Value(0), Value(1)]);
+        assert_eq!(bits("parity"), [Value(1), Value(0), Value(0), Value(1)]);
+        assert_eq!(c.signal("cc"), Value(1));
+
+        c.set_value("input", 0)?;
+        c.step()?;
+        assert_eq!(c.signal("a"), Value(0));
+        assert_eq!(c.signal("parity"), Value(0));
+
+        c.reset_registers();
+        c.compute_signals()?;
+        assert_eq!(c.signal("a"), Value(0));
+        assert_eq!(c.signal("b"), Value(1));
+        assert_eq!(c.signal("cc"), Value(0));
+
+        // A register reading a missing wire latches an uncomputable signal
+        c.add_wire("REG missing -> r")?;
+        c.step()?;
+        assert_eq!(c.signal("r"), Signal::Uncomputable);
+        c.remove_wire_then_reset_signals("r")?;
+
+        // Loops must go through a register
+        c.replace_wire("parity -> input")?;
+        assert!(c.evaluation_order().is_ok());
+        c.step()?;
+        c.replace_wire("REG parity -> a")?;
+        c.replace_wire("ab -> b")?;
+        match c.step() {
+            Err(Error::CircuitLoop(cycle)) => assert_eq!(cycle, ["ab", "b"]),
+            _ => panic!("loop not detected"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn write_read_registers() -> Result<()> {
+        let c = Circuit::try_from("REG d INIT 3 -> q\nq ADD 1 -> d:16\nREG q -> p")?;
+        let s = c.to_string();
+        assert!(s.contains("REG d INIT 3 -> q"));
+        assert!(Circuit::try_from(s.as_str())?.equals(&c));
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(8)?
+            .add_wire("1 -> a")?
+            .add_wire("X -> unused")?
+            .add_wire("a ADD b -> c")?
+            .add_wire("c AND 3 -> d")?
+            .add_wire("d OR e -> f")?
+            .add_wire("f -> e")?
+            .add_wire("REG g -> h")?
+            .add_wire("h XOR 1 -> g")?
+            .add_wire("NOT x -> y")?
+            .add_wire("NOT y -> x")?
+            .build();
+        let problems = match c.validate() {
+            Err(Error::InvalidCircuit(problems)) => problems,
+            _ => panic!("invalid circuit not detected"),
+        };
+        assert_eq!(problems.len(), 4);
+        assert!(matches!(&problems[0], Error::UndrivenWire(id) if id == "b"));
+        assert!(matches!(&problems[1], Error::CircuitLoop(ids) if ids == &["e", "f"]));
+        assert!(matches!(&problems[2], Error::CircuitLoop(ids) if ids == &["x", "y"]));
+        assert!(matches!(&problems[3], Error::UnusedWire(id) if id == "unused"));
+
+        c.remove_wire_then_reset_signals("unused")?;
+        c.replace_wire("d -> e")?;
+        c.replace_wire("NOT 1 -> x")?;
+        // Widths are checked as wires are added, so break them behind the circuit's back
+        let mut wires = mem::take(&mut c.wires);
+        wires.insert(WireId::new("b")?, Wire::parse("a SUB 1 -> b:4")?);
+        wires.insert(WireId::new("w")?, Wire::parse("b RSHIFT 6 -> w:4")?);
+        c.set_wires(wires);
+        let problems: Vec<String> = match c.validate() {
+            Err(Error::InvalidCircuit(problems)) => problems.iter().map(Error::to_string).collect(),
+            _ => panic!("invalid circuit not detected"),
+        };
+        assert_eq!(
+            problems,
+            [
+                "Width of wire 'b' does not match that of its input 'a'",
+                "Width of wire 'c' does not match that of its input 'b'",
+                "Wire 'w' violates its width: Shift amount '6' is not less than the signal width"
+            ]
+        );
+
+        c.remove_wire_then_reset_signals("w")?;
+        c.replace_wire("a SUB 1 -> b")?;
+        c.validate()?;
+        let mut builder = CircuitBuilder::new();
+        builder.add_wire("a -> b")?;
+        assert!(matches!(builder.try_build(), Err(Error::InvalidCircuit(_))));
+        let c = builder.add_wire("1 -> a")?.try_build()?;
+        assert_eq!(c.get_wires().len(), 2);
+        assert!(CircuitBuilder::new()
+            .add_wire("1 -> a")?
+            .add_wire("a -> b")?
+            .try_build()
+            .is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn comparison_gates() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire_with_value("x", 7)?
+            .add_wire_with_value("y", 9)?
+            .add_gate_eq("eq", "x", "y")?
+            .add_gate_ne("ne", "x", "y")?
+            .add_gate_lt("lt", "x", "y")?
+            .add_gate_le("le", "y", "x")?
+            .add_gate_gt("gt", "x", "y")?
+            .add_gate_ge("ge", "y", "x")?
+            .add_gate_eq_value("eqv", "x", 7)?
+            .add_wire("7 LT x -> ltv")?
+            .add_wire("9 GE x -> gev")?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("eq"), Signal::Value(0));
+        assert_eq!(c.signal("ne"), Signal::Value(1));
+        assert_eq!(c.signal("lt"), Signal::Value(1));
+        assert_eq!(c.signal("le"), Signal::Value(0));
+        assert_eq!(c.signal("gt"), Signal::Value(0));
+        assert_eq!(c.signal("ge"), Signal::Value(1));
+        assert_eq!(c.signal("eqv"), Signal::Value(1));
+        assert_eq!(c.signal("ltv"), Signal::Value(0));
+        assert_eq!(c.signal("gev"), Signal::Value(1));
+        Ok(())
+    }
+
+    #[test]
+    fn signal_width() -> Result<()> {
+        let mut c = Circuit::with_width(32)?;
+        c.add_wire("4294967295 -> x")?;
+        c.add_wire("x ADD 1 -> y")?;
+        c.add_wire("NOT y -> z")?;
+        c.add_wire("x LSHIFT 31 -> l")?;
+        c.add_wire("x ROTL 16 -> r")?;
+        c.compute_signals()?;
+
+        assert_eq!(c.width(), 32);
+        assert_eq!(c.signal("x"), Signal::Value(0xffff_ffff));
+        assert_eq!(c.signal("y"), Signal::Value(0));
+        assert_eq!(c.signal("z"), Signal::Value(0xffff_ffff));
+        assert_eq!(c.signal("l"), Signal::Value(0x8000_0000));
+        assert_eq!(c.signal("r"), Signal::Value(0xffff_ffff));
+        assert!(matches!(
+            c.add_wire("4294967296 -> w"),
+            Err(Error::TooLargeValue(4294967296))
+        ));
+        assert!(matches!(
+            c.add_gate_rshift("w", "x", 32),
+            Err(Error::TooLargeShift(32))
+        ));
+
+        let mut c = CircuitBuilder::with_width(64)?
+            .add_wire_with_value("x", 0)?
+            .add_gate_not("nx", "x")?
+            .add_gate_sub_value("dec", "x", 1)?
+            .build();
+        c.compute_signals()?;
+        assert_eq!(c.signal("nx"), Signal::Value(u64::MAX));
+        assert_eq!(c.signal("dec"), Signal::Value(u64::MAX));
+
+        let mut c = Circuit::with_width(8)?;
+        assert!(matches!(
+            c.add_wire_with_value("x", 256),
+            Err(Error::TooLargeValue(256))
+        ));
+        assert!(matches!(
+            c.add_gate_and_value("x", "y", 256),
+            Err(Error::TooLargeValue(256))
+        ));
+        assert!(matches!(
+            c.add_wire("y LSHIFT 8 -> x"),
+            Err(Error::TooLargeShift(8))
+        ));
+
+        assert!(matches!(
+            Circuit::with_width(0),
+            Err(Error::InvalidWidth(0))
+        ));
+        assert!(matches!(
+            CircuitBuilder::with_width(65),
+            Err(Error::InvalidWidth(65))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn read_nanocorp_2_with_width() -> Result<()> {
+        let mut c1 = Circuit::read("circuits/nanocorp_2.txt")?;
+        let mut c2 = Circuit::read_with_width("circuits/nanocorp_2.txt", 64)?;
+        c1.compute_signals()?;
+        c2.compute_signals()?;
+        assert_eq!(c1.width(), 16);
+        assert_eq!(c2.width(), 64);
+        assert!(matches!(c1.signal("ku"), Signal::Value(v) if v <= 0xffff));
+        assert!(matches!(c2.signal("ku"), Signal::Value(v) if v > 0xffff));
+        Ok(())
+    }
+
+    #[test]
+    fn wire_widths() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("200 -> x:8")?
+            .add_wire("100 -> y:8")?
+            .add_wire("x ADD y -> sum:8")?
+            .add_wire("NOT x -> nx:8")?
+            .add_wire("3 -> n:2")?
+            .add_wire("x ROTL n -> r:8")?
+            .add_wire("x LT y -> lt")?
+            .add_wire("sum -> [7:0] copy")?
+            .add_wire("1000 -> z")?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.width_of("x")?, 8);
+        assert_eq!(c.width_of("z")?, 16);
+        assert_eq!(c.signal("sum"), Signal::Value(44));
+        assert_eq!(c.signal("nx"), Signal::Value(55));
+        assert_eq!(c.signal("r"), Signal::Value(0x46));
+        assert_eq!(c.signal("lt"), Signal::Value(0));
+        assert_eq!(c.signal("copy"), Signal::Value(44));
+
+        assert!(matches!(
+            c.add_wire("x AND z -> w:8"),
+            Err(Error::WidthMismatch(w, z)) if w == "w" && z == "z"
+        ));
+        assert!(matches!(
+            c.add_wire("x OR y -> w"),
+            Err(Error::WidthMismatch(w, x)) if w == "w" && x == "x"
+        ));
+        assert!(matches!(
+            c.add_wire("x EQ z -> w"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        assert!(matches!(
+            c.add_wire("z -> w:8"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        assert!(matches!(
+            c.add_wire("n MUX x z -> w:8"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        c.add_wire("n MUX x y -> w:8")?;
+
+        // Readers added before the wire they read are checked too
+        c.add_wire("u AND x -> v:8")?;
+        assert!(matches!(
+            c.add_wire("1 -> u"),
+            Err(Error::WidthMismatch(v, u)) if v == "v" && u == "u"
+        ));
+        c.add_wire("1 -> u:8")?;
+        Ok(())
+    }
+
+    #[test]
+    fn set_width_of() -> Result<()> {
+        let mut c = Circuit::new();
+        c.add_wire("511 -> x")?;
+        c.add_wire("NOT x -> y")?;
+        c.add_wire("4 -> z")?;
+        c.compute_signals()?;
+
+        assert!(matches!(
+            c.set_width_of("x", 8),
+            Err(Error::TooLargeValue(511))
+        ));
+        assert!(matches!(
+            c.set_width_of("y", 12),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        c.set_width_of("z", 3)?;
+        assert_eq!(c.signal("x"), Signal::Value(511));
+        assert_eq!(c.signal("z"), Signal::Uncomputed);
+        c.compute_signals()?;
+        assert_eq!(c.width_of("z")?, 3);
+        assert_eq!(c.signal("z"), Signal::Value(4));
+
+        let c = CircuitBuilder::new()
+            .add_wire("1 -> x")?
+            .set_width_of("x", 1)?
+            .add_wire("x -> y:1")?
+            .build();
+        assert_eq!(c.width_of("x")?, 1);
+        assert!(matches!(
+            CircuitBuilder::new()
+                .add_wire("1 -> x")?
+                .add_wire("x -> y")?
+                .set_width_of("x", 1),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn write_read_wire_widths() -> Result<()> {
+        let c1 = Circuit::try_from(
+            "x AND y -> d:4\n\
+             7 -> x:4\n\
+             12 -> [3:0] y",
+        )?;
+        c1.write("circuits/wire_widths.txt")?;
+        let c2 = Circuit::read("circuits/wire_widths.txt")?;
+        fs::remove_file("circuits/wire_widths.txt")?;
+        assert!(c1.equals(&c2));
+        Ok(())
+    }
+
+    #[test]
+    fn write_read_width() -> Result<()> {
+        let mut c1 = Circuit::with_width(32)?;
+        c1.add_wire("100000 -> x")?;
+        c1.add_wire("x ADD 1 -> y")?;
+        c1.write("circuits/width_32.txt")?;
+        let c2 = Circuit::read("circuits/width_32.txt")?;
+        let c3 = Circuit::read_with_width("circuits/width_32.txt", 8)?;
+        fs::remove_file("circuits/width_32.txt")?;
+        assert!(c1.to_string().starts_with("WIDTH 32\n"));
+        assert!(c1.equals(&c2));
+        assert!(c1.equals(&c3));
+        assert!(!Circuit::new().to_string().contains("WIDTH"));
+
+        assert_eq!(Circuit::try_from("WIDTH 4\n15 -> x")?.width(), 4);
+        assert!(matches!(
+            Circuit::try_from("WIDTH 4\n16 -> x"),
+            Err(Error::TooLargeValue(16))
+        ));
+        assert!(matches!(
+            Circuit::try_from("WIDTH four\n1 -> x"),
+            Err(Error::ParseWidth(_))
+        ));
+        assert!(matches!(
+            Circuit::try_from("WIDTH 65\n1 -> x"),
+            Err(Error::InvalidWidth(65))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn slice_and_concat() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("43981 -> x")?
+            .add_wire("x[15:8] -> hi:8")?
+            .add_wire("x[7:0] -> lo:8")?
+            .add_wire("x[0] -> bit:1")?
+            .add_wire("{lo, hi} -> swapped")?
+            .add_wire("{bit, lo} -> w:9")?
+            .add_gate_slice("mid", "x", 11, 4)?
+            .add_gate_concat("all", ["bit", "bit", "bit"])?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("hi"), Signal::Value(0xab));
+        assert_eq!(c.signal("lo"), Signal::Value(0xcd));
+        assert_eq!(c.signal("bit"), Signal::Value(1));
+        assert_eq!(c.signal("swapped"), Signal::Value(0xcdab));
+        assert_eq!(c.signal("w"), Signal::Value(0x1cd));
+        assert_eq!(c.signal("mid"), Signal::Value(0xbc));
+        assert_eq!(c.signal("all"), Signal::Value(0b111));
+
+        assert!(matches!(
+            c.add_wire("hi[8] -> v"),
+            Err(Error::WidthMismatch(v, hi)) if v == "v" && hi == "hi"
+        ));
+        assert!(matches!(
+            c.add_wire("x[15:8] -> v:7"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        assert!(matches!(
+            c.add_wire("{hi, lo, bit} -> v"),
+            Err(Error::WidthMismatch(v, bit)) if v == "v" && bit == "bit"
+        ));
+        assert!(matches!(
+            c.add_gate_slice("v", "x", 3, 4),
+            Err(Error::InvalidBitRange(3, 4))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn constant_operands() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(8)?
+            .add_wire("1 AND 3 -> a")?
+            .add_wire("NOT 5 -> b")?
+            .add_wire("2 LSHIFT 3 -> c")?
+            .add_wire("a RSHIFT 0 -> d")?
+            .add_wire("10 SUB c -> e")?
+            .add_wire("1 LSHIFT d -> f")?
+            .add_wire("0 MUX a f -> g")?
+            .build();
+        c.compute_signals()?;
+
+        assert_eq!(c.signal("a"), Signal::Value(1));
+        assert_eq!(c.signal("b"), Signal::Value(0xfa));
+        assert_eq!(c.signal("c"), Signal::Value(16));
+        assert_eq!(c.signal("d"), Signal::Value(1));
+        assert_eq!(c.signal("e"), Signal::Value(250));
+        assert_eq!(c.signal("f"), Signal::Value(2));
+        assert_eq!(c.signal("g"), Signal::Value(2));
+        assert_eq!(c.four_state_signals()?["e"].value(), Some(250));
+
+        assert!(matches!(
+            c.add_wire("300 OR 1 -> h"),
+            Err(Error::TooLargeValue(300))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn four_state() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(8)?
+            .add_wire("X -> x")?
+            .add_wire("Z -> z")?
+            .add_wire("15 -> a")?
+            .add_wire("z -> bus")?
+            .add_wire("a AND u -> au")?
+            .add_wire("au AND 48 -> zero")?
+            .add_wire("x OR a -> xa")?
+            .add_wire("xa ADD 1 -> inc")?
+            .add_wire("z XOR a -> za")?
+            .add_wire("zero EQ 0 -> ok")?
+            .add_wire("ok MUX a u -> res")?
+            .add_wire("x[3:0] -> lo:4")?
+            .add_wire("{lo, a} -> wide:12")?
+            .build();
+
+        let signals = c.four_state_signals()?;
+        assert_eq!(signals.len(), 13);
+        assert_eq!(signals["x"].to_string(), "xxxxxxxx");
+        assert_eq!(signals["bus"].to_string(), "zzzzzzzz");
+        assert_eq!(signals["au"].to_string(), "0000xxxx");
+        assert_eq!(signals["zero"].value(), Some(0));
+        assert_eq!(signals["xa"].to_string(), "xxxx1111");
+        assert_eq!(signals["inc"].to_string(), "xxxx0000");
+        assert_eq!(signals["za"].to_string(), "xxxxxxxx");
+        assert_eq!(signals["ok"].value(), Some(1));
+        assert_eq!(signals["res"].value(), Some(15));
+        assert_eq!(signals["lo"].to_string(), "xxxx");
+        assert_eq!(signals["wide"].to_string(), "xxxx00001111");
+        assert_eq!(signals["wide"].unknown_bits(), 0xf00);
+        assert_eq!(signals["bus"].high_impedance_bits(), 0xff);
+
+        c.compute_signals()?;
+        assert_eq!(c.signal("x"), Signal::Uncomputable);
+        assert_eq!(c.signal("res"), Signal::Uncomputable);
+        assert_eq!(c.signal("a"), Signal::Value(15));
+
+        c.add_wire("res AND p -> q")?;
+        c.add_wire("q OR a -> p")?;
+        assert!(matches!(c.four_state_signals(), Err(Error::CircuitLoop(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluation_order() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("b AND c -> a")?
+            .add_wire("1 -> c")?
+            .add_wire("d -> b")?
+            .add_wire("NOT c -> e")?
+            .build();
+        assert_eq!(c.evaluation_order()?, ["b", "c", "a", "e"]);
+        assert!(c.order.get().is_some());
+
+        c.add_wire("2 -> d")?;
+        assert!(c.order.get().is_none());
+        assert_eq!(c.evaluation_order()?, ["c", "d", "b", "a", "e"]);
+        c.set_input("d", "e")?;
+        assert_eq!(c.evaluation_order()?, ["c", "e", "d", "b", "a"]);
+
+        assert_eq!(c.compute_signal("b")?, Signal::Value(0xfffe));
+        assert_eq!(c.signal("a"), Signal::Uncomputed);
+        c.compute_signals()?;
+        assert_eq!(c.signal("a"), Signal::Value(0));
+
+        c.set_input("c", "a")?;
+        assert!(matches!(c.evaluation_order(), Err(Error::CircuitLoop(_))));
+        assert!(matches!(c.compute_signals(), Err(Error::CircuitLoop(_))));
+        c.remove_wire_then_reset_signals("a")?;
+        c.compute_signals()?;
+        assert_eq!(c.signal("e"), Signal::Uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn compute_signal_next_to_loop() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("b -> a")?
+            .add_wire("a -> b")?
+            .add_wire("1 -> z")?
+            .add_wire("NOT z -> y")?
+            .add_wire("y AND a -> x")?
+            .build();
+        assert_eq!(c.compute_signal("z")?, Signal::Value(1));
+        assert_eq!(c.compute_signal("y")?, Signal::Value(0xfffe));
+        assert_eq!(c.signal("x"), Signal::Uncomputed);
+        match c.compute_signal("x") {
+            Err(Error::CircuitLoop(ids)) => assert_eq!(ids, ["a", "b"]),
+            other => panic!("expected a loop, got {:?}", other),
+        }
+        assert!(matches!(c.compute_signals(), Err(Error::CircuitLoop(_))));
+        Ok(())
+    }
+
     #[test]
     fn loop_2_wires() -> Result<()> {
         let mut c = Circuit::new();
@@ -727,6 +3016,127 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn incremental_recomputation() -> Result<()> {
+        let mut c = CircuitBuilder::new()
+            .add_wire("3 -> a")?
+            .add_wire("5 -> b")?
+            .add_wire("a AND b -> ab")?
+            .add_wire("NOT a -> na")?
+            .add_wire("ab OR b -> abb")?
+            .add_wire("na ADD 1 -> nega")?
+            .build();
+        c.compute_signals()?;
+        assert!(c.uncomputed.is_empty());
+
+        c.set_value("b", 6)?;
+        let mut uncomputed: Vec<String> = c.uncomputed.iter().map(WireId::to_string).collect();
+        uncomputed.sort();
+        assert_eq!(uncomputed, ["ab", "abb", "b"]);
+        assert_eq!(c.signal("na"), Signal::Value(0xfffc));
+        assert_eq!(c.signal("abb"), Signal::Uncomputed);
+        c.compute_signals()?;
+        assert_eq!(c.signal("ab"), Signal::Value(2));
+        assert_eq!(c.signal("abb"), Signal::Value(6));
+
+        c.replace_wire("b NOT -> na").unwrap_err();
+        c.replace_wire("NOT b -> na")?;
+        assert_eq!(c.signal("nega"), Signal::Uncomputed);
+        assert_eq!(c.signal("ab"), Signal::Value(2));
+        c.compute_signals()?;
+        assert_eq!(c.signal("nega"), Signal::Value(0xfffa));
+
+        c.remove_wire_then_reset_signals("a")?;
+        assert_eq!(c.signal("b"), Signal::Value(6));
+        c.compute_signals()?;
+        assert_eq!(c.signal("ab"), Signal::Uncomputable);
+        assert_eq!(c.signal("abb"), Signal::Uncomputable);
+        assert_eq!(c.signal("nega"), Signal::Value(0xfffa));
+
+        c.add_wire("1 -> a")?;
+        c.compute_signals()?;
+        assert_eq!(c.signal("abb"), Signal::Value(6));
+        assert!(c.uncomputable.is_empty());
+
+        assert!(matches!(c.set_value("q", 1), Err(Error::UnknownWireId(_))));
+        assert!(matches!(
+            c.set_value("a", 0x10000),
+            Err(Error::TooLargeValue(0x10000))
+        ));
+        assert!(matches!(
+            c.replace_wire("ab -> nega:8"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn override_inputs() -> Result<()> {
+        let mut c = Circuit::read("circuits/nanocorp_2.txt")?;
+        c.compute_signals()?;
+        let a = match c.signal("a") {
+            Signal::Value(a) => a,
+            signal => panic!("{:?}", signal),
+        };
+        c.set_value("b", a)?;
+        c.compute_signals()?;
+
+        let mut expected = Circuit::try_from(
+            fs::read_to_string("circuits/nanocorp_2.txt")?
+                .replace("19138 -> b", &format!("{} -> b", a))
+                .as_str(),
+        )?;
+        expected.compute_signals()?;
+        assert!(c.equals(&expected));
+        assert!(c.uncomputed.is_empty());
+        assert!(c.uncomputable.is_empty());
+
+        let mut c = CircuitBuilder::new()
+            .add_wire("1 -> x")?
+            .add_wire("2 -> y")?
+            .add_wire("5 -> n:8")?
+            .add_wire("NOT x -> z")?
+            .build();
+        c.compute_signals()?;
+
+        c.set_input_from_wire("x", "y")?;
+        c.compute_signals()?;
+        assert_eq!(c.signal("z"), Signal::Value(0xfffd));
+
+        c.set_input("x", "y ADD u")?;
+        c.compute_signals()?;
+        assert_eq!(c.signal("z"), Signal::Uncomputable);
+        assert_eq!(c.uncomputable.len(), 2);
+
+        c.set_input("x", "y ADD 1")?;
+        c.compute_signals()?;
+        assert_eq!(c.signal("z"), Signal::Value(0xfffc));
+        assert!(c.uncomputable.is_empty());
+
+        c.set_input("n", "7")?;
+        assert_eq!(c.width_of("n")?, 8);
+        assert!(matches!(
+            c.set_input("n", "300"),
+            Err(Error::TooLargeValue(300))
+        ));
+        assert!(matches!(
+            c.set_input("n", "x"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        assert!(matches!(
+            c.set_input_from_wire("x", "x"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            c.set_input("w", "x"),
+            Err(Error::UnknownWireId(_))
+        ));
+
+        c.set_input("y", "z")?;
+        assert!(matches!(c.compute_signals(), Err(Error::CircuitLoop(_))));
+        Ok(())
+    }
+
     #[test]
     fn compute_signals_then_add_wire() -> Result<()> {
         let mut c = Circuit::new();
@@ -773,4 +3183,49 @@ mod tests {
         assert_eq!(c.signal("nz"), Signal::Value(0xfeef));
         Ok(())
     }
+
+    #[test]
+    fn graph_queries() -> Result<()> {
+        let c = CircuitBuilder::with_width(8)?
+            .add_wire("1 -> a")?
+            .add_wire("2 -> b")?
+            .add_wire("a AND b -> ab")?
+            .add_wire("ab OR a -> x")?
+            .add_wire("x XOR missing -> y")?
+            .add_register("state", "x", 0)?
+            .add_wire("state ADD ab -> z")?
+            .build();
+
+        assert_eq!(c.inputs_of("x")?, ["ab", "a"]);
+        assert_eq!(c.inputs_of("state")?, ["x"]);
+        assert!(c.inputs_of("a")?.is_empty());
+        assert!(matches!(
+            c.inputs_of("missing"),
+            Err(Error::UnknownWireId(_))
+        ));
+
+        assert_eq!(c.fanout_of("a")?, ["ab", "x"]);
+        assert_eq!(c.fanout_of("missing")?, ["y"]);
+        assert!(c.fanout_of("z")?.is_empty());
+        assert!(matches!(
+            c.fanout_of("nothing"),
+            Err(Error::UnknownWireId(_))
+        ));
+        assert!(matches!(c.fanout_of("A"), Err(Error::InvalidWireId(_))));
+
+        assert_eq!(c.transitive_fanin("y")?, ["a", "ab", "b", "missing", "x"]);
+        assert_eq!(c.transitive_fanin("z")?, ["a", "ab", "b", "state", "x"]);
+        assert!(c.transitive_fanin("missing")?.is_empty());
+        assert_eq!(c.transitive_fanout("b")?, ["ab", "state", "x", "y", "z"]);
+        assert_eq!(c.transitive_fanout("missing")?, ["y"]);
+        assert!(c.transitive_fanout("y")?.is_empty());
+
+        let mut inputs: Vec<&str> = c.primary_inputs().collect();
+        inputs.sort();
+        assert_eq!(inputs, ["a", "b"]);
+        let mut outputs: Vec<&str> = c.primary_outputs().collect();
+        outputs.sort();
+        assert_eq!(outputs, ["y", "z"]);
+        Ok(())
+    }
 }
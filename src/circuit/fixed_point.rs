@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use super::Circuit;
+use crate::wire::{signal::Signal, wire_id::WireId};
+
+/// The outcome of a [fixed-point evaluation](Circuit::compute_signals_fixed_point).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Convergence {
+    /// Signals stopped changing after this number of iterations
+    Converged(usize),
+    /// Signals cycle through this number of states (at least 2)
+    Oscillating(usize),
+    /// Signals neither converged nor repeated within the iteration limit
+    LimitReached,
+}
+
+impl Circuit {
+    /// Computes the signals of a circuit which may have loops by iterating
+    /// until they settle, instead of failing with [`Error::CircuitLoop`](crate::Error::CircuitLoop).
+    /// Starting from the current signals of the wires (0 for those which are not computed),
+    /// each iteration computes the signals of all wires from those of the previous iteration.
+    /// This goes on until the signals of an iteration are those of a previous one,
+    /// which is a fixed point if that is the previous iteration and an oscillation otherwise,
+    /// or until it is clear that no state repeats within `max_iterations` iterations.
+    /// Cycles are detected with Brent's algorithm, which only keeps a couple of states
+    /// in memory at the cost of up to 4 times the iterations needed to reach
+    /// the first repeated state.
+    /// The circuit is left with the signals of the first repeated state if one was found,
+    /// and with the last signals computed otherwise.
+    /// Wires reading wires which are not in the circuit are uncomputable as usual.
+    /// For a circuit without loops, this gives the same signals as
+    /// [`compute_signals()`](Self::compute_signals).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Convergence, Signal, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// // An SR latch being set
+    /// let mut latch = CircuitBuilder::with_width(1)?
+    ///     .add_wire("1 -> s")?
+    ///     .add_wire("0 -> r")?
+    ///     .add_wire("r NOR qn -> q")?
+    ///     .add_wire("s NOR q -> qn")?
+    ///     .build();
+    /// assert_eq!(latch.compute_signals_fixed_point(10), Convergence::Converged(3));
+    /// assert_eq!(latch.signal("q"), Signal::Value(1));
+    ///
+    /// // A ring oscillator
+    /// let mut ring = CircuitBuilder::with_width(1)?
+    ///     .add_wire("NOT c -> a")?
+    ///     .add_wire("NOT a -> b")?
+    ///     .add_wire("NOT b -> c")?
+    ///     .build();
+    /// assert_eq!(ring.compute_signals_fixed_point(10), Convergence::Oscillating(2));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compute_signals_fixed_point(&mut self, max_iterations: usize) -> Convergence {
+        let mut ids: Vec<WireId> = self.wires.keys().cloned().collect();
+        ids.sort();
+        let indices: HashMap<&WireId, usize> =
+            ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+        let signals: Vec<Signal> = ids
+            .iter()
+            .map(|id| match self.signal_of(id) {
+                Signal::Value(value) => Signal::Value(value),
+                _ => Signal::Value(0),
+            })
+            .collect();
+
+        let next = |signals: &[Signal]| -> Vec<Signal> {
+            ids.iter()
+                .map(|id| {
+                    self.evaluate_from(self.wire_of(id), |input| {
+                        indices
+                            .get(input)
+                            .map_or(Signal::Uncomputable, |i| signals[*i])
+                    })
+                })
+                .collect()
+        };
+
+        // Brent's cycle detection: the states are only compared with a saved one,
+        // saved again whenever the number of iterations since it reaches a power of 2.
+        // A repeated state is found within 3 times the iterations needed to reach
+        // the first one, and locating the start of the cycle takes as many iterations
+        // as reaching it, so up to 4 times in all
+        let (convergence, signals) = if max_iterations == 0 {
+            (Convergence::LimitReached, signals)
+        } else {
+            let mut saved = signals.clone();
+            let mut hare = next(&signals);
+            let (mut power, mut period, mut iterations) = (1, 1, 1);
+            while saved != hare && iterations < max_iterations.saturating_mul(3) {
+                if power == period {
+                    saved = hare.clone();
+                    power *= 2;
+                    period = 0;
+                }
+                hare = next(&hare);
+                period += 1;
+                iterations += 1;
+            }
+            if saved != hare {
+                (Convergence::LimitReached, hare)
+            } else {
+                // The first repeated state comes `period` iterations after the start
+                // of the cycle, found by iterating from the initial state
+                // and from `period` iterations later at once until they meet
+                let mut start = 0;
+                let mut tortoise = signals;
+                let mut hare = tortoise.clone();
+                for _ in 0..period {
+                    hare = next(&hare);
+                }
+                while tortoise != hare {
+                    tortoise = next(&tortoise);
+                    hare = next(&hare);
+                    start += 1;
+                }
+                let convergence = match period {
+                    _ if start + period > max_iterations => Convergence::LimitReached,
+                    1 => Convergence::Converged(start),
+                    period => Convergence::Oscillating(period),
+                };
+                (convergence, hare)
+            }
+        };
+
+        self.uncomputable.clear();
+        for (id, signal) in ids.iter().zip(signals) {
+            if signal == Signal::Uncomputable {
+                self.uncomputable.push(id.to_owned());
+            }
+            self.set_signal_of(id, signal).unwrap();
+        }
+        self.uncomputed.clear();
+        convergence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, CircuitBuilder};
+
+    #[test]
+    fn acyclic_circuit() -> Result<()> {
+        let mut circuit = Circuit::read("circuits/nanocorp_2.txt")?;
+        circuit.add_wire("a AND missing -> zz")?;
+        let mut expected = circuit.clone();
+        expected.compute_signals()?;
+        assert!(matches!(
+            circuit.compute_signals_fixed_point(1000),
+            Convergence::Converged(_)
+        ));
+        assert!(circuit.equals(&expected));
+        assert_eq!(circuit.uncomputable, expected.uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn loops() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(4)?
+            .add_wire("1 -> one")?
+            .add_wire("x ADD one -> y")?
+            .add_wire("y AND 3 -> x")?
+            .build();
+        assert_eq!(
+            c.compute_signals_fixed_point(100),
+            Convergence::Oscillating(8)
+        );
+        assert_eq!(c.compute_signals_fixed_point(2), Convergence::LimitReached);
+        assert_eq!(c.compute_signals_fixed_point(7), Convergence::LimitReached);
+        assert_eq!(
+            c.compute_signals_fixed_point(8),
+            Convergence::Oscillating(8)
+        );
+        assert_eq!(c.compute_signals_fixed_point(0), Convergence::LimitReached);
+
+        c.replace_wire("y AND 0 -> x")?;
+        assert!(matches!(
+            c.compute_signals_fixed_point(100),
+            Convergence::Converged(1 | 2)
+        ));
+        assert_eq!(c.signal("x"), Signal::Value(0));
+        assert_eq!(c.signal("y"), Signal::Value(1));
+
+        c.replace_wire("x ADD z -> y")?;
+        assert_eq!(
+            c.compute_signals_fixed_point(100),
+            Convergence::Converged(2)
+        );
+        assert_eq!(c.signal("x"), Signal::Uncomputable);
+        assert_eq!(c.uncomputable.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, num::NonZeroUsize, thread};
+
+use super::Circuit;
+use crate::{
+    error::Result,
+    wire::{signal::Signal, wire_id::WireId},
+};
+
+// Smallest number of wires worth evaluating in a thread of its own
+const MIN_WIRES_PER_THREAD: usize = 256;
+
+impl Circuit {
+    /// Parallel version of [`compute_signals()`](Self::compute_signals),
+    /// available with feature `parallel`.  
+    /// The wires are grouped into levels, a wire being one level above the highest
+    /// of its inputs, so that the wires of a level only read wires of lower levels.
+    /// Levels are computed one after the other, the wires of each level being split
+    /// among scoped threads (one per available core at most).
+    /// Signals are the same as those computed by [`compute_signals()`](Self::compute_signals).  
+    /// This only pays off for large circuits: levels of fewer than a few hundred wires
+    /// are computed by the calling thread.  
+    /// Returns error if the circuit has a loop.
+    pub fn compute_signals_parallel(&mut self) -> Result<()> {
+        if self.uncomputed.is_empty() {
+            return Ok(());
+        }
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        for level in self.levels()? {
+            let uncomputed: Vec<&WireId> = level
+                .iter()
+                .filter(|id| *self.wire_of(id).signal() == Signal::Uncomputed)
+                .collect();
+            let wires_per_thread = uncomputed.len().div_ceil(threads).max(MIN_WIRES_PER_THREAD);
+            let signals: Vec<Signal> = if uncomputed.len() <= wires_per_thread {
+                self.evaluate_all(&uncomputed)
+            } else {
+                let circuit = &*self;
+                thread::scope(|scope| {
+                    let handles: Vec<_> = uncomputed
+                        .chunks(wires_per_thread)
+                        .map(|chunk| scope.spawn(move || circuit.evaluate_all(chunk)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .flat_map(|handle| handle.join().unwrap())
+                        .collect()
+                })
+            };
+            for (id, signal) in uncomputed.into_iter().zip(signals) {
+                if signal == Signal::Uncomputable {
+                    self.uncomputable.push(id.to_owned());
+                }
+                self.set_signal_of(id, signal).unwrap();
+            }
+        }
+        self.uncomputable.sort();
+        self.uncomputable.dedup();
+        self.uncomputed.clear();
+        Ok(())
+    }
+
+    // Groups the wires by level: wires which read no wire of the circuit are on level 0
+    // and any other wire is one level above the highest of its inputs
+    fn levels(&self) -> Result<Vec<Vec<WireId>>> {
+        let mut depths: HashMap<&WireId, usize> = HashMap::with_capacity(self.wires.len());
+        let mut levels: Vec<Vec<WireId>> = Vec::new();
+        for id in self.order()? {
+            let depth = self
+                .wire_of(id)
+                .combinational_inputs()
+                .into_iter()
+                .filter_map(|input| depths.get(input))
+                .map(|depth| depth + 1)
+                .max()
+                .unwrap_or(0);
+            depths.insert(id, depth);
+            if depth == levels.len() {
+                levels.push(Vec::new());
+            }
+            levels[depth].push(id.to_owned());
+        }
+        Ok(levels)
+    }
+
+    // Computes the signals of `ids`, whose inputs are all computed
+    fn evaluate_all(&self, ids: &[&WireId]) -> Vec<Signal> {
+        ids.iter()
+            .map(|id| self.evaluate(self.wire_of(id)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that both evaluators compute the same signals
+    fn assert_same_signals(circuit: &Circuit) -> Result<()> {
+        let mut sequential = circuit.clone();
+        sequential.compute_signals()?;
+        let mut parallel = circuit.clone();
+        parallel.compute_signals_parallel()?;
+        assert!(parallel.equals(&sequential));
+        assert_eq!(parallel.uncomputable, sequential.uncomputable);
+        Ok(())
+    }
+
+    #[test]
+    fn levels() -> Result<()> {
+        let circuit = Circuit::try_from("1 -> a\nNOT a -> b\nNOT b -> c\na AND c -> d\nx -> e")?;
+        let levels: Vec<Vec<String>> = circuit
+            .levels()?
+            .iter()
+            .map(|level| level.iter().map(WireId::to_string).collect())
+            .collect();
+        assert_eq!(levels, [vec!["a", "e"], vec!["b"], vec!["c"], vec!["d"]]);
+        Ok(())
+    }
+
+    #[test]
+    fn nanocorp_2() -> Result<()> {
+        assert_same_signals(&Circuit::read("circuits/nanocorp_2.txt")?)
+    }
+
+    #[test]
+    fn large_circuit() -> Result<()> {
+        let mut circuit = Circuit::new();
+        let id = |level: usize, i: usize| {
+            let letter = |n: usize| char::from(b'a' + (n % 26) as u8);
+            format!(
+                "w{}{}{}{}",
+                letter(level),
+                letter(i / 676),
+                letter(i / 26),
+                letter(i)
+            )
+        };
+        for i in 0..1000 {
+            circuit.add_wire_with_value(id(0, i), i as u64)?;
+        }
+        for level in 1..8 {
+            for i in 0..1000 {
+                let (a, b) = (id(level - 1, i), id(level - 1, (i * 7 + 1) % 1000));
+                match i % 5 {
+                    0 => circuit.add_gate_add(id(level, i), a, b)?,
+                    1 => circuit.add_gate_xor(id(level, i), a, b)?,
+                    2 => circuit.add_gate_div(id(level, i), a, b)?,
+                    3 => circuit.add_gate_lshift(id(level, i), a, 3)?,
+                    _ => circuit.add_gate_or(id(level, i), a, "z")?,
+                }
+            }
+        }
+        assert_same_signals(&circuit)?;
+
+        circuit.compute_signals_parallel()?;
+        circuit.set_value(id(0, 0), 42)?;
+        let mut sequential = circuit.clone();
+        sequential.compute_signals()?;
+        circuit.compute_signals_parallel()?;
+        assert!(circuit.equals(&sequential));
+        Ok(())
+    }
+}
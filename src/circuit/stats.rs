@@ -0,0 +1,186 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+use super::Circuit;
+use crate::wire::wire_input::WireInput;
+
+/// Metrics of a circuit, returned by [`stats()`](Circuit::stats).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CircuitStats {
+    /// Number of wires in the circuit
+    pub wires: usize,
+    /// Number of gates of each kind present in the circuit, kinds being gate keywords
+    /// such as `"AND"`, `"SLICE"` for bit ranges and `"CONCAT"` for concatenations
+    pub gates: BTreeMap<&'static str, usize>,
+    /// Number of wires whose input is a value
+    pub value_inputs: usize,
+    /// Number of wires whose input is another wire
+    pub wire_inputs: usize,
+    /// Number of wires whose input is a gate
+    pub gate_inputs: usize,
+    /// Number of registers
+    pub register_inputs: usize,
+    /// Number of wires whose input is an unknown signal
+    pub unknown_inputs: usize,
+    /// Number of wires whose input is a high-impedance signal
+    pub high_impedance_inputs: usize,
+    /// Largest number of wires reading a same wire of the circuit
+    pub max_fanout: usize,
+    /// Average number of wires reading a wire of the circuit
+    pub average_fanout: f64,
+    /// Largest [logic depth](Circuit::logic_depths) of a wire, `None` if the circuit has a loop
+    pub depth: Option<usize>,
+    /// Number of wires which neither read nor are read by any other wire
+    pub unused_wires: usize,
+}
+
+impl CircuitStats {
+    /// Returns the total number of gates.
+    pub fn gate_count(&self) -> usize {
+        self.gates.values().sum()
+    }
+}
+
+impl Display for CircuitStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Wires: {}", self.wires)?;
+        writeln!(f, "  value inputs: {}", self.value_inputs)?;
+        writeln!(f, "  wire inputs: {}", self.wire_inputs)?;
+        writeln!(f, "  gate inputs: {}", self.gate_inputs)?;
+        writeln!(f, "  registers: {}", self.register_inputs)?;
+        writeln!(f, "  unknown inputs: {}", self.unknown_inputs)?;
+        writeln!(f, "  high-impedance inputs: {}", self.high_impedance_inputs)?;
+        writeln!(f, "Gates: {}", self.gate_count())?;
+        for (kind, count) in &self.gates {
+            writeln!(f, "  {}: {}", kind, count)?;
+        }
+        writeln!(
+            f,
+            "Fanout: max {}, average {:.2}",
+            self.max_fanout, self.average_fanout
+        )?;
+        match self.depth {
+            Some(depth) => writeln!(f, "Depth: {}", depth)?,
+            None => writeln!(f, "Depth: none (circuit has a loop)")?,
+        }
+        writeln!(f, "Unused wires: {}", self.unused_wires)
+    }
+}
+
+impl Circuit {
+    /// Returns metrics of the circuit: gate counts per kind, numbers of wires per kind
+    /// of input, fanout, depth and number of unused wires.
+    /// Fanouts only count wires of the circuit, not the undriven wires they read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let stats = CircuitBuilder::new()
+    ///     .add_wire("1 -> a")?
+    ///     .add_wire("NOT a -> b")?
+    ///     .add_wire("a AND b -> c")?
+    ///     .build()
+    ///     .stats();
+    /// assert_eq!(stats.gates["AND"], 1);
+    /// assert_eq!(stats.max_fanout, 2);
+    /// assert_eq!(stats.depth, Some(2));
+    /// println!("{}", stats);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> CircuitStats {
+        let mut stats = CircuitStats {
+            wires: self.wires.len(),
+            depth: self
+                .logic_depths()
+                .ok()
+                .map(|depths| depths.into_values().max().unwrap_or(0)),
+            ..CircuitStats::default()
+        };
+        let mut total_fanout = 0;
+        for (id, wire) in &self.wires {
+            match wire.input() {
+                WireInput::Value(_) => stats.value_inputs += 1,
+                WireInput::Wire(_) => stats.wire_inputs += 1,
+                WireInput::Gate(gate) => {
+                    stats.gate_inputs += 1;
+                    *stats.gates.entry(gate.kind()).or_insert(0) += 1;
+                }
+                WireInput::Register { .. } => stats.register_inputs += 1,
+                WireInput::Unknown => stats.unknown_inputs += 1,
+                WireInput::HighImpedance => stats.high_impedance_inputs += 1,
+            }
+            let fanout = self.fanout.get(id).map_or(0, |readers| readers.len());
+            stats.max_fanout = stats.max_fanout.max(fanout);
+            total_fanout += fanout;
+            if self.is_unused(wire) {
+                stats.unused_wires += 1;
+            }
+        }
+        if !self.wires.is_empty() {
+            stats.average_fanout = total_fanout as f64 / self.wires.len() as f64;
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, CircuitBuilder};
+
+    #[test]
+    fn stats() -> Result<()> {
+        let mut c = CircuitBuilder::with_width(8)?
+            .add_wire("1 -> a")?
+            .add_wire("2 -> b")?
+            .add_wire("a -> copy")?
+            .add_wire("a AND b -> ab")?
+            .add_wire("NOT ab -> nab")?
+            .add_wire("copy AND nab -> x")?
+            .add_wire("x OR missing -> y")?
+            .add_wire("a[3:0] -> low")?
+            .add_register("state", "y", 0)?
+            .add_wire_unknown("unknown")?
+            .add_wire_high_impedance("z")?
+            .build();
+        let stats = c.stats();
+        assert_eq!(stats.wires, 11);
+        assert_eq!(
+            stats.gates,
+            BTreeMap::from([("AND", 2), ("NOT", 1), ("OR", 1), ("SLICE", 1)])
+        );
+        assert_eq!(stats.gate_count(), 5);
+        assert_eq!(stats.value_inputs, 2);
+        assert_eq!(stats.wire_inputs, 1);
+        assert_eq!(stats.gate_inputs, 5);
+        assert_eq!(stats.register_inputs, 1);
+        assert_eq!(stats.unknown_inputs, 1);
+        assert_eq!(stats.high_impedance_inputs, 1);
+        assert_eq!(stats.max_fanout, 3);
+        assert_eq!(stats.average_fanout, 9.0 / 11.0);
+        assert_eq!(stats.depth, Some(4));
+        assert_eq!(stats.unused_wires, 2);
+        assert_eq!(
+            stats.to_string(),
+            "Wires: 11\n  value inputs: 2\n  wire inputs: 1\n  gate inputs: 5\n  \
+             registers: 1\n  unknown inputs: 1\n  high-impedance inputs: 1\n\
+             Gates: 5\n  AND: 2\n  NOT: 1\n  OR: 1\n  SLICE: 1\n\
+             Fanout: max 3, average 0.82\nDepth: 4\nUnused wires: 2\n"
+        );
+
+        c.add_wire("NOT loop -> pool")?;
+        c.add_wire("NOT pool -> loop")?;
+        assert_eq!(c.stats().depth, None);
+
+        let empty = Circuit::new().stats();
+        assert_eq!(empty.wires, 0);
+        assert_eq!(empty.average_fanout, 0.0);
+        assert_eq!(empty.depth, Some(0));
+        Ok(())
+    }
+}
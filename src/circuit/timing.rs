@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use super::Circuit;
+use crate::{
+    error::{Error, Result},
+    wire::{gate::Gate, wire_id::WireId, wire_input::WireInput},
+};
+
+/// The longest path through a circuit, found by [`critical_path()`](Circuit::critical_path).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CriticalPath {
+    /// Total delay of the gates along the path
+    pub delay: u64,
+    /// Wires of the path, from a primary input (or a register, or an undriven wire)
+    /// to the wire where the path ends
+    pub steps: Vec<PathStep>,
+}
+
+/// A wire of a [`CriticalPath`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PathStep {
+    /// Id of the wire
+    pub wire: String,
+    /// Gate driving the wire, such as `"a AND b"`, if any
+    pub gate: Option<String>,
+    /// Delay of the gates from the start of the path up to the wire included
+    pub arrival: u64,
+}
+
+impl Display for CriticalPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Critical path (delay {}):", self.delay)?;
+        for step in &self.steps {
+            match &step.gate {
+                Some(gate) => writeln!(f, "  {} -> {} @ {}", gate, step.wire, step.arrival)?,
+                None => writeln!(f, "  {} @ {}", step.wire, step.arrival)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Circuit {
+    /// Returns the logic depth of each wire, the number of gates on the longest path
+    /// from a primary input to the wire.
+    /// Wires driven by a value, an unknown or high-impedance signal or a register
+    /// have depth 0, as well as wires reading undriven wires only.
+    /// Wires copying another wire have the depth of that wire.
+    /// Returns an error if the circuit has a loop.
+    pub fn logic_depths(&self) -> Result<HashMap<String, usize>> {
+        Ok(self
+            .arrivals(|_| 1)?
+            .into_iter()
+            .map(|(id, (depth, _))| (id.to_string(), depth as usize))
+            .collect())
+    }
+
+    /// Returns the longest path through the circuit, each gate on it delaying signals
+    /// by the weight of its kind in `delays` (see [`logic_depths()`](Self::logic_depths)).
+    /// Kinds are gate keywords such as `"AND"` or `"MUX"`, `"SLICE"` for bit ranges
+    /// and `"CONCAT"` for concatenations. Gates whose kind is not in `delays` weigh 1,
+    /// so with no delays the delay of the path is the largest logic depth.
+    /// Among paths of equal delay, the one ending on the first wire in alphabetical order
+    /// is returned.
+    /// Returns an error if a kind in `delays` is unknown or if the circuit has a loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use circuitry::{CircuitBuilder, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let circuit = CircuitBuilder::new()
+    ///     .add_wire("1 -> a")?
+    ///     .add_wire("2 -> b")?
+    ///     .add_wire("a MUL b -> m")?
+    ///     .add_wire("NOT a -> na")?
+    ///     .add_wire("NOT na -> nna")?
+    ///     .build();
+    /// let path = circuit.critical_path(&HashMap::new())?;
+    /// assert_eq!(path.delay, 2);
+    /// assert_eq!(path.steps.last().unwrap().wire, "nna");
+    ///
+    /// let path = circuit.critical_path(&HashMap::from([("MUL", 5)]))?;
+    /// assert_eq!(path.delay, 5);
+    /// assert_eq!(path.steps[1].gate.as_deref(), Some("a MUL b"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn critical_path(&self, delays: &HashMap<&str, u64>) -> Result<CriticalPath> {
+        if let Some(kind) = delays.keys().find(|kind| !Gate::KINDS.contains(kind)) {
+            return Err(Error::UnknownGateKind(kind.to_string()));
+        }
+        let arrivals = self.arrivals(|gate| *delays.get(gate.kind()).unwrap_or(&1))?;
+        let end = arrivals
+            .iter()
+            .max_by(|(id1, (arrival1, _)), (id2, (arrival2, _))| {
+                arrival1.cmp(arrival2).then(id2.cmp(id1))
+            })
+            .map(|(id, _)| *id);
+
+        let mut path = CriticalPath::default();
+        let mut step = end;
+        while let Some(id) = step {
+            let (arrival, previous) = arrivals.get(id).copied().unwrap_or((0, None));
+            let gate = self.wires.get(id).and_then(|wire| match wire.input() {
+                WireInput::Gate(gate) => Some(gate.to_string()),
+                _ => None,
+            });
+            path.steps.push(PathStep {
+                wire: id.to_string(),
+                gate,
+                arrival,
+            });
+            step = previous;
+        }
+        path.steps.reverse();
+        path.delay = path.steps.last().map_or(0, |step| step.arrival);
+        Ok(path)
+    }
+
+    // Returns for each wire the largest delay of the gates on a path ending on it,
+    // with the input of the wire this path goes through if any.
+    // Undriven inputs have no entry and count as arriving at 0.
+    fn arrivals<F>(&self, delay: F) -> Result<HashMap<&WireId, (u64, Option<&WireId>)>>
+    where
+        F: Fn(&Gate) -> u64,
+    {
+        let mut arrivals: HashMap<&WireId, (u64, Option<&WireId>)> =
+            HashMap::with_capacity(self.wires.len());
+        for id in self.order()? {
+            let wire = self.wire_of(id);
+            let mut latest: Option<(u64, &WireId)> = None;
+            for input in wire.combinational_inputs() {
+                let arrival = arrivals.get(input).map_or(0, |(arrival, _)| *arrival);
+                latest = match latest {
+                    Some((latest, _)) if latest >= arrival => continue,
+                    _ => Some((arrival, input)),
+                };
+            }
+            let start = latest.map_or(0, |(arrival, _)| arrival);
+            let arrival = match wire.input() {
+                WireInput::Gate(gate) => start + delay(gate),
+                _ => start,
+            };
+            arrivals.insert(id, (arrival, latest.map(|(_, input)| input)));
+        }
+        Ok(arrivals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CircuitBuilder;
+
+    #[test]
+    fn logic_depths() -> Result<()> {
+        let c = CircuitBuilder::with_width(8)?
+            .add_wire("1 -> a")?
+            .add_wire("a -> copy")?
+            .add_wire("NOT copy -> b")?
+            .add_wire("b AND a -> c")?
+            .add_wire("c OR missing -> d")?
+            .add_register("state", "d", 0)?
+            .add_wire("NOT state -> e")?
+            .build();
+        let depths = c.logic_depths()?;
+        assert_eq!(depths.len(), 7);
+        assert_eq!(depths["a"], 0);
+        assert_eq!(depths["copy"], 0);
+        assert_eq!(depths["b"], 1);
+        assert_eq!(depths["c"], 2);
+        assert_eq!(depths["d"], 3);
+        assert_eq!(depths["state"], 0);
+        assert_eq!(depths["e"], 1);
+
+        let looping = CircuitBuilder::new()
+            .add_wire("NOT y -> x")?
+            .add_wire("NOT x -> y")?
+            .build();
+        assert!(matches!(looping.logic_depths(), Err(Error::CircuitLoop(_))));
+        assert_eq!(Circuit::new().critical_path(&HashMap::new())?.steps, []);
+        Ok(())
+    }
+
+    #[test]
+    fn critical_path() -> Result<()> {
+        let c = CircuitBuilder::with_width(8)?
+            .add_wire("1 -> a")?
+            .add_wire("2 -> b")?
+            .add_wire("a -> copy")?
+            .add_wire("copy MUL b -> m")?
+            .add_wire("NOT a -> na")?
+            .add_wire("NOT na -> nna")?
+            .add_wire("nna XOR m -> x")?
+            .build();
+
+        let path = c.critical_path(&HashMap::new())?;
+        assert_eq!(path.delay, 3);
+        let wires: Vec<&str> = path.steps.iter().map(|step| step.wire.as_str()).collect();
+        assert_eq!(wires, ["a", "na", "nna", "x"]);
+        assert_eq!(path.steps[0].gate, None);
+        assert_eq!(path.steps[3].gate.as_deref(), Some("nna XOR m"));
+
+        let path = c.critical_path(&HashMap::from([("MUL", 4), ("XOR", 2)]))?;
+        assert_eq!(path.delay, 6);
+        let wires: Vec<&str> = path.steps.iter().map(|step| step.wire.as_str()).collect();
+        assert_eq!(wires, ["a", "copy", "m", "x"]);
+        let arrivals: Vec<u64> = path.steps.iter().map(|step| step.arrival).collect();
+        assert_eq!(arrivals, [0, 0, 4, 6]);
+        assert_eq!(
+            path.to_string(),
+            "Critical path (delay 6):\n  a @ 0\n  copy @ 0\n  copy MUL b -> m @ 4\n  \
+             nna XOR m -> x @ 6\n"
+        );
+
+        assert!(matches!(
+            c.critical_path(&HashMap::from([("FOO", 1)])),
+            Err(Error::UnknownGateKind(_))
+        ));
+        Ok(())
+    }
+}
@@ -1,7 +1,10 @@
-use std::{collections::HashMap, mem};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
 
 use super::{
-    wire::{wire_id::WireId, Wire},
+    wire::{width::Width, wire_id::WireId, Wire},
     Circuit,
 };
 use crate::error::{Error, Result};
@@ -29,13 +32,16 @@ use crate::error::{Error, Result};
 /// # Ok(())
 /// # }
 /// ```
-
+///
 /// You can also use method [`add_wire()`](Self::add_wire)
 /// with string representation if you prefer.
 /// See [example](Circuit#example-1) for usage.
 #[derive(Clone, Debug, Default)]
 pub struct CircuitBuilder {
+    width: Width,
     wires: HashMap<WireId, Wire>,
+    // Ids of the wires reading each wire, to check widths without scanning all wires
+    readers: HashMap<WireId, HashSet<WireId>>,
 }
 
 impl CircuitBuilder {
@@ -44,38 +50,126 @@ impl CircuitBuilder {
         Self::default()
     }
 
+    /// Creates an empty builder for signals `width` bits wide.  
+    /// Equivalent of [`Circuit::with_width`].
+    pub fn with_width(width: u8) -> Result<Self> {
+        Ok(Self {
+            width: Width::new(width)?,
+            ..Self::default()
+        })
+    }
+
     /// Final call method building the circuit from the builder.
     pub fn build(&mut self) -> Circuit {
         let mut circuit = Circuit::new();
+        circuit.set_width(self.width);
         circuit.set_wires(mem::take(&mut self.wires));
+        self.readers.clear();
         circuit.set_uncomputed(circuit.get_wires().keys().cloned().collect());
         circuit
     }
 
+    /// Builds the circuit like [`build()`](Self::build),
+    /// then [validates](Circuit::validate) it.  
+    /// Returns an error listing all the problems found if the circuit is invalid,
+    /// in which case the builder keeps its wires so that they can be fixed.
+    pub fn try_build(&mut self) -> Result<Circuit> {
+        let circuit = self.build();
+        if let Err(error) = circuit.validate() {
+            for wire in circuit.into_wires().into_values() {
+                self.insert(wire);
+            }
+            return Err(error);
+        }
+        Ok(circuit)
+    }
+
     fn add(&mut self, wire: Wire) -> Result<&mut CircuitBuilder> {
         if self.wires.contains_key(wire.id()) {
             Err(Error::WireIdAlreadyExists(wire.id().to_string()))
         } else {
-            self.wires.insert(wire.id().to_owned(), wire);
+            Circuit::check_widths(&self.wires, self.width, &wire, self.readers_of(wire.id()))?;
+            self.insert(wire);
             Ok(self)
         }
     }
 
+    // Inserts `wire` as a reader of its inputs then as a wire of the builder
+    fn insert(&mut self, wire: Wire) {
+        for input in wire.inputs() {
+            self.readers
+                .entry(input.to_owned())
+                .or_default()
+                .insert(wire.id().to_owned());
+        }
+        self.wires.insert(wire.id().to_owned(), wire);
+    }
+
+    /// Equivalent of [`Circuit::set_width_of`].
+    pub fn set_width_of<S: Into<String>>(
+        &mut self,
+        id: S,
+        width: u8,
+    ) -> Result<&mut CircuitBuilder> {
+        let id = WireId::new(id)?;
+        let mut wire = self
+            .wires
+            .get(&id)
+            .ok_or(Error::UnknownWireId(id.to_string()))?
+            .clone();
+        wire.set_width(Some(Width::new(width)?));
+        Circuit::check_widths(&self.wires, self.width, &wire, self.readers_of(&id))?;
+        self.wires.insert(id, wire);
+        Ok(self)
+    }
+
+    // Returns the wires of the builder which read wire `id`
+    fn readers_of<'a>(&'a self, id: &WireId) -> impl Iterator<Item = &'a Wire> + 'a {
+        self.readers
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|reader| self.wires.get(reader))
+    }
+
     /// Adds a wire whose string representation is `s`.
     /// See [example](Circuit#example-1) for usage.
     pub fn add_wire(&mut self, s: &str) -> Result<&mut CircuitBuilder> {
-        self.add(Wire::try_from(s)?)
+        self.add(Wire::parse(s)?)
     }
 
     /// Equivalent of [`Circuit::add_wire_with_value`].
     pub fn add_wire_with_value<S: Into<String>>(
         &mut self,
         id: S,
-        value: u16,
+        value: u64,
     ) -> Result<&mut CircuitBuilder> {
         self.add(Wire::with_value(id, value)?)
     }
 
+    /// Equivalent of [`Circuit::add_wire_unknown`].
+    pub fn add_wire_unknown<S: Into<String>>(&mut self, id: S) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::unknown(id)?)
+    }
+
+    /// Equivalent of [`Circuit::add_wire_high_impedance`].
+    pub fn add_wire_high_impedance<S: Into<String>>(
+        &mut self,
+        id: S,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::high_impedance(id)?)
+    }
+
+    /// Equivalent of [`Circuit::add_register`].
+    pub fn add_register<S: Into<String>, T: Into<String>>(
+        &mut self,
+        id: S,
+        input_id: T,
+        initial: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::register(id, input_id, initial)?)
+    }
+
     /// Equivalent of [`Circuit::add_wire_from_wire`].
     pub fn add_wire_from_wire<S: Into<String>, T: Into<String>>(
         &mut self,
@@ -100,7 +194,7 @@ impl CircuitBuilder {
         &mut self,
         output: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<&mut CircuitBuilder> {
         self.add(Wire::from_gate_and_value(output, input, value)?)
     }
@@ -120,11 +214,311 @@ impl CircuitBuilder {
         &mut self,
         output: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<&mut CircuitBuilder> {
         self.add(Wire::from_gate_or_value(output, input, value)?)
     }
 
+    /// Equivalent of [`Circuit::add_gate_xor`].
+    pub fn add_gate_xor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_xor(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_xor_value`].
+    pub fn add_gate_xor_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_xor_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_nand`].
+    pub fn add_gate_nand<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_nand(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_nand_value`].
+    pub fn add_gate_nand_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_nand_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_nor`].
+    pub fn add_gate_nor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_nor(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_nor_value`].
+    pub fn add_gate_nor_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_nor_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_xnor`].
+    pub fn add_gate_xnor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_xnor(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_xnor_value`].
+    pub fn add_gate_xnor_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_xnor_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_add`].
+    pub fn add_gate_add<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_add(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_add_value`].
+    pub fn add_gate_add_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_add_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_sub`].
+    pub fn add_gate_sub<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_sub(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_sub_value`].
+    pub fn add_gate_sub_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_sub_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_mul`].
+    pub fn add_gate_mul<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_mul(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_mul_value`].
+    pub fn add_gate_mul_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_mul_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_div`].
+    pub fn add_gate_div<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_div(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_div_value`].
+    pub fn add_gate_div_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_div_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_mod`].
+    pub fn add_gate_mod<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_mod(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_mod_value`].
+    pub fn add_gate_mod_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_mod_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_eq`].
+    pub fn add_gate_eq<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_eq(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_eq_value`].
+    pub fn add_gate_eq_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_eq_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_ne`].
+    pub fn add_gate_ne<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_ne(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_ne_value`].
+    pub fn add_gate_ne_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_ne_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_lt`].
+    pub fn add_gate_lt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_lt(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_lt_value`].
+    pub fn add_gate_lt_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_lt_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_le`].
+    pub fn add_gate_le<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_le(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_le_value`].
+    pub fn add_gate_le_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_le_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_gt`].
+    pub fn add_gate_gt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_gt(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_gt_value`].
+    pub fn add_gate_gt_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_gt_value(output, input, value)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_ge`].
+    pub fn add_gate_ge<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input1: T,
+        input2: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_ge(output, input1, input2)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_ge_value`].
+    pub fn add_gate_ge_value<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        value: u64,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_ge_value(output, input, value)?)
+    }
+
     /// Equivalent of [`Circuit::add_gate_lshift`].
     pub fn add_gate_lshift<S: Into<String>, T: Into<String>>(
         &mut self,
@@ -145,6 +539,66 @@ impl CircuitBuilder {
         self.add(Wire::from_gate_rshift(output, input, shift)?)
     }
 
+    /// Equivalent of [`Circuit::add_gate_lshift_wire`].
+    pub fn add_gate_lshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_lshift_wire(output, input, shift)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_rshift_wire`].
+    pub fn add_gate_rshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_rshift_wire(output, input, shift)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_rotl`].
+    pub fn add_gate_rotl<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_rotl(output, input, shift)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_rotl_wire`].
+    pub fn add_gate_rotl_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_rotl_wire(output, input, shift)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_rotr`].
+    pub fn add_gate_rotr<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: u8,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_rotr(output, input, shift)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_rotr_wire`].
+    pub fn add_gate_rotr_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        shift: U,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_rotr_wire(output, input, shift)?)
+    }
+
     /// Equivalent of [`Circuit::add_gate_not`].
     pub fn add_gate_not<S: Into<String>, T: Into<String>>(
         &mut self,
@@ -153,6 +607,37 @@ impl CircuitBuilder {
     ) -> Result<&mut CircuitBuilder> {
         self.add(Wire::from_gate_not(output, input)?)
     }
+
+    /// Equivalent of [`Circuit::add_gate_slice`].
+    pub fn add_gate_slice<S: Into<String>, T: Into<String>>(
+        &mut self,
+        output: S,
+        input: T,
+        msb: u8,
+        lsb: u8,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_slice(output, input, msb, lsb)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_concat`].
+    pub fn add_gate_concat<S: Into<String>, I: IntoIterator<Item = T>, T: Into<String>>(
+        &mut self,
+        output: S,
+        inputs: I,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_concat(output, inputs)?)
+    }
+
+    /// Equivalent of [`Circuit::add_gate_mux`].
+    pub fn add_gate_mux<S: Into<String>, T: Into<String>, U: Into<String>, V: Into<String>>(
+        &mut self,
+        output: S,
+        select: T,
+        input1: U,
+        input2: V,
+    ) -> Result<&mut CircuitBuilder> {
+        self.add(Wire::from_gate_mux(output, select, input1, input2)?)
+    }
 }
 
 #[cfg(test)]
@@ -212,4 +697,22 @@ mod tests {
         assert!(c1.equals(&c2));
         Ok(())
     }
+
+    #[test]
+    fn reader_widths() -> Result<()> {
+        let mut builder = CircuitBuilder::new();
+        builder.add_wire("x AND y -> z:8")?.add_wire("1 -> y:8")?;
+        assert!(matches!(
+            builder.add_wire("1 -> x:4"),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        assert!(matches!(
+            builder.set_width_of("y", 4),
+            Err(Error::WidthMismatch(_, _))
+        ));
+        builder.add_wire("1 -> x:8")?;
+        assert_eq!(builder.build().get_wires().len(), 3);
+        assert!(builder.readers.is_empty());
+        Ok(())
+    }
 }
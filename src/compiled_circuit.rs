@@ -0,0 +1,552 @@
+use std::collections::{HashMap, HashSet};
+
+use super::wire::{
+    gate::Gate, operand::Operand, signal::Signal, width::Width, wire_id::WireId,
+    wire_input::WireInput,
+};
+use crate::{
+    circuit::Circuit,
+    error::{Error, Result},
+};
+
+/// An immutable circuit compiled for fast repeated evaluation
+///
+/// Built by [`Circuit::compile()`], a compiled circuit identifies its wires
+/// by dense indices (their position in the [evaluation order](Circuit::evaluation_order))
+/// and holds one instruction per wire, so that evaluating it never looks up a wire id.
+///
+/// Its inputs are the wires of the circuit whose input is a value or which are registers
+/// (their signals being the state of the circuit)
+/// and its outputs the wires which are not read by any other wire, both sorted by id.
+/// Evaluating the compiled circuit with the values of its inputs gives the same signals
+/// as setting those values in the circuit then calling
+/// [`compute_signals()`](Circuit::compute_signals).
+///
+/// # Example
+///
+/// ```
+/// # use circuitry::{CircuitBuilder, Signal, Error};
+/// # fn main() -> Result<(), Error> {
+/// let compiled = CircuitBuilder::new()
+///     .add_wire_with_value("a", 0)?
+///     .add_wire_with_value("b", 0)?
+///     .add_gate_and("ab", "a", "b")?
+///     .build()
+///     .compile()?;
+///
+/// assert_eq!(compiled.input_ids(), ["a", "b"]);
+/// assert_eq!(compiled.output_ids(), ["ab"]);
+/// assert_eq!(compiled.evaluate(&[1729, 4936])?, [Signal::Value(1729 & 4936)]);
+///
+/// // Evaluation without allocation, reusing the same buffer
+/// let mut signals = compiled.signals_buffer();
+/// compiled.evaluate_into(&[3, 6], &mut signals)?;
+/// assert_eq!(signals[compiled.index_of("ab")?], Signal::Value(2));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompiledCircuit {
+    ids: Vec<WireId>,
+    instructions: Vec<Instruction>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+// Computes the signal of the wire at the same index
+#[derive(Clone, Debug)]
+struct Instruction {
+    width: Width,
+    op: Op,
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    /// Value given at this position of the inputs
+    Input(usize),
+    /// Signal of the wire at this index
+    Copy(usize),
+    /// Gate whose operands are indices of wires or constant values
+    Gate {
+        gate: Gate,
+        operands: Vec<Source>,
+        widths: Vec<Width>,
+    },
+    /// Wire reading a wire which is not in the circuit or an X or Z value
+    Uncomputable,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Source {
+    Index(usize),
+    Value(u64),
+}
+
+impl CompiledCircuit {
+    pub(super) fn new(circuit: &Circuit) -> Result<Self> {
+        let ids = circuit.order()?.clone();
+        let indices: HashMap<&WireId, usize> =
+            ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+        let wires = circuit.get_wires();
+        let width = circuit.get_width();
+
+        let mut input_ids: Vec<&WireId> = ids
+            .iter()
+            .filter(|id| {
+                matches!(
+                    wires[*id].input(),
+                    WireInput::Value(_) | WireInput::Register { .. }
+                )
+            })
+            .collect();
+        input_ids.sort();
+        let input_positions: HashMap<&WireId, usize> = input_ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect();
+
+        let instructions = ids
+            .iter()
+            .map(|id| {
+                let wire = &wires[id];
+                let op = match wire.input() {
+                    WireInput::Value(_) | WireInput::Register { .. } => {
+                        Op::Input(input_positions[id])
+                    }
+                    WireInput::Unknown | WireInput::HighImpedance => Op::Uncomputable,
+                    WireInput::Wire(input) => match indices.get(input) {
+                        Some(index) => Op::Copy(*index),
+                        None => Op::Uncomputable,
+                    },
+                    WireInput::Gate(gate) => {
+                        let sources: Option<Vec<(Source, Width)>> = gate
+                            .operands()
+                            .into_iter()
+                            .map(|operand| match operand {
+                                Operand::Value(value) => Some((Source::Value(*value), width)),
+                                Operand::Wire(input) => indices.get(input).map(|index| {
+                                    (Source::Index(*index), wires[input].width_or(width))
+                                }),
+                            })
+                            .collect();
+                        match sources {
+                            Some(sources) => Op::Gate {
+                                gate: gate.clone(),
+                                operands: sources.iter().map(|(source, _)| *source).collect(),
+                                widths: sources.iter().map(|(_, width)| *width).collect(),
+                            },
+                            None => Op::Uncomputable,
+                        }
+                    }
+                };
+                Instruction {
+                    width: wire.width_or(width),
+                    op,
+                }
+            })
+            .collect();
+
+        let read: HashSet<&WireId> = wires.values().flat_map(|wire| wire.inputs()).collect();
+        let mut output_ids: Vec<&WireId> = ids.iter().filter(|id| !read.contains(id)).collect();
+        output_ids.sort();
+
+        Ok(Self {
+            inputs: input_ids.iter().map(|id| indices[id]).collect(),
+            outputs: output_ids.iter().map(|id| indices[id]).collect(),
+            instructions,
+            ids,
+        })
+    }
+
+    /// Returns the number of wires.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the circuit has no wire.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the index of wire `id`.
+    /// Returns an error if `id` is not ascii lowercase or if the circuit has no such wire.
+    pub fn index_of<S: Into<String>>(&self, id: S) -> Result<usize> {
+        let id = WireId::new(id)?;
+        self.ids
+            .iter()
+            .position(|other| *other == id)
+            .ok_or(Error::UnknownWireId(id.to_string()))
+    }
+
+    /// Returns the ids of all wires, by index.
+    pub fn ids(&self) -> Vec<String> {
+        self.ids.iter().map(WireId::to_string).collect()
+    }
+
+    /// Returns the ids of the inputs, in the order their values are expected.
+    pub fn input_ids(&self) -> Vec<String> {
+        self.inputs
+            .iter()
+            .map(|i| self.ids[*i].to_string())
+            .collect()
+    }
+
+    /// Returns the ids of the outputs, in the order their signals are returned.
+    pub fn output_ids(&self) -> Vec<String> {
+        self.outputs
+            .iter()
+            .map(|i| self.ids[*i].to_string())
+            .collect()
+    }
+
+    /// Returns a buffer large enough to hold the signals of all wires,
+    /// to be filled by [`evaluate_into()`](Self::evaluate_into).
+    pub fn signals_buffer(&self) -> Vec<Signal> {
+        vec![Signal::Uncomputed; self.len()]
+    }
+
+    /// Evaluates the circuit with the values of its inputs and returns the signals
+    /// of its outputs, in the order of [`input_ids()`](Self::input_ids)
+    /// and [`output_ids()`](Self::output_ids).
+    /// Use [`evaluate_into()`](Self::evaluate_into) to evaluate without allocating.
+    /// Returns an error if the number of values is not the number of inputs
+    /// or if a value does not fit in the width of its input.
+    pub fn evaluate(&self, inputs: &[u64]) -> Result<Vec<Signal>> {
+        let mut signals = self.signals_buffer();
+        self.evaluate_into(inputs, &mut signals)?;
+        Ok(self.outputs.iter().map(|i| signals[*i]).collect())
+    }
+
+    /// Evaluates the circuit with the values of its inputs and writes the signals
+    /// of all wires into `signals`, by index (see [`index_of()`](Self::index_of)).
+    /// This does not allocate, so that the same buffer can be reused across evaluations.
+    /// Returns an error if the number of values is not the number of inputs,
+    /// if a value does not fit in the width of its input
+    /// or if `signals` is not as long as the number of wires.
+    pub fn evaluate_into(&self, inputs: &[u64], signals: &mut [Signal]) -> Result<()> {
+        self.check_inputs(inputs)?;
+        if signals.len() != self.len() {
+            return Err(Error::BufferLength(self.len(), signals.len()));
+        }
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            signals[index] = match &instruction.op {
+                Op::Input(position) => Signal::Value(inputs[*position]),
+                Op::Copy(input) => signals[*input],
+                Op::Uncomputable => Signal::Uncomputable,
+                Op::Gate {
+                    gate,
+                    operands,
+                    widths,
+                } => gate_signal(gate, operands, widths, instruction.width, |input| {
+                    signals[input]
+                }),
+            };
+        }
+        Ok(())
+    }
+
+    /// Evaluates the circuit with each assignment of values to its inputs
+    /// and returns the table of the signals of its outputs, one row per assignment
+    /// in the order of [`input_ids()`](Self::input_ids) and [`output_ids()`](Self::output_ids).  
+    /// All assignments are evaluated in a single pass over the instructions:
+    /// the values of a wire for all assignments (its lanes) are stored side by side
+    /// with a bitmask of the lanes where it is computable, and each gate is computed
+    /// over all lanes at once, in a tight loop the compiler can vectorize.
+    /// Signals are the same as those given by [`evaluate()`](Self::evaluate)
+    /// for each assignment.  
+    /// Returns an error if an assignment does not have as many values as there are inputs
+    /// or if a value does not fit in the width of its input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use circuitry::{CircuitBuilder, Signal, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// let compiled = CircuitBuilder::with_width(2)?
+    ///     .add_wire("0 -> a")?
+    ///     .add_wire("0 -> b")?
+    ///     .add_wire("a DIV b -> q")?
+    ///     .build()
+    ///     .compile()?;
+    ///
+    /// let assignments: Vec<[u64; 2]> = (0..16).map(|i| [i >> 2, i & 3]).collect();
+    /// let table = compiled.evaluate_batch(&assignments)?;
+    /// assert_eq!(table.len(), 16);
+    /// assert_eq!(table[0], [Signal::Uncomputable]);
+    /// assert_eq!(table[13], [Signal::Value(3)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn evaluate_batch<I: AsRef<[u64]>>(&self, assignments: &[I]) -> Result<Vec<Vec<Signal>>> {
+        for inputs in assignments {
+            self.check_inputs(inputs.as_ref())?;
+        }
+        let lanes = assignments.len();
+        let words = lanes.div_ceil(64);
+        let mut values = vec![0u64; self.len() * lanes];
+        let mut computable = vec![0u64; self.len() * words];
+        let all_lanes: Vec<u64> = (0..words)
+            .map(|word| match lanes - word * 64 {
+                remaining if remaining < 64 => (1 << remaining) - 1,
+                _ => u64::MAX,
+            })
+            .collect();
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            let (computed, rest) = values.split_at_mut(index * lanes);
+            let row = &mut rest[..lanes];
+            let (computed_mask, rest) = computable.split_at_mut(index * words);
+            let row_mask = &mut rest[..words];
+            match &instruction.op {
+                Op::Input(position) => {
+                    for (value, inputs) in row.iter_mut().zip(assignments) {
+                        *value = inputs.as_ref()[*position];
+                    }
+                    row_mask.copy_from_slice(&all_lanes);
+                }
+                Op::Copy(input) => {
+                    row.copy_from_slice(&computed[input * lanes..(input + 1) * lanes]);
+                    row_mask.copy_from_slice(&computed_mask[input * words..(input + 1) * words]);
+                }
+                Op::Uncomputable => {}
+                Op::Gate {
+                    gate,
+                    operands,
+                    widths,
+                } => {
+                    row_mask.copy_from_slice(&all_lanes);
+                    let constants: Vec<Vec<u64>> = operands
+                        .iter()
+                        .filter_map(|source| match source {
+                            Source::Value(value) => Some(vec![*value; lanes]),
+                            Source::Index(_) => None,
+                        })
+                        .collect();
+                    let mut constants = constants.iter();
+                    let inputs: Vec<&[u64]> = operands
+                        .iter()
+                        .map(|source| match source {
+                            Source::Index(input) => {
+                                let input_mask = &computed_mask[input * words..(input + 1) * words];
+                                for (mask, input_mask) in row_mask.iter_mut().zip(input_mask) {
+                                    *mask &= input_mask;
+                                }
+                                &computed[input * lanes..(input + 1) * lanes]
+                            }
+                            Source::Value(_) => constants.next().unwrap().as_slice(),
+                        })
+                        .collect();
+                    gate.compute_lanes(&inputs, widths, instruction.width, row);
+                    if gate.divides() {
+                        for (lane, divisor) in inputs[1].iter().enumerate() {
+                            if *divisor == 0 {
+                                row_mask[lane / 64] &= !(1 << (lane % 64));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok((0..lanes)
+            .map(|lane| {
+                self.outputs
+                    .iter()
+                    .map(|output| {
+                        if computable[output * words + lane / 64] & (1 << (lane % 64)) != 0 {
+                            Signal::Value(values[output * lanes + lane])
+                        } else {
+                            Signal::Uncomputable
+                        }
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    // Checks that there is one value per input and that each value fits in its input
+    fn check_inputs(&self, inputs: &[u64]) -> Result<()> {
+        if inputs.len() != self.inputs.len() {
+            return Err(Error::InputCount(self.inputs.len(), inputs.len()));
+        }
+        for (value, index) in inputs.iter().zip(&self.inputs) {
+            self.instructions[*index].width.check_value(*value)?;
+        }
+        Ok(())
+    }
+}
+
+// Computes the signal of `gate` given the signals of the wires by index.
+// The gate is uncomputable if one of its input wires is
+fn gate_signal<F: Fn(usize) -> Signal>(
+    gate: &Gate,
+    operands: &[Source],
+    widths: &[Width],
+    width: Width,
+    signal: F,
+) -> Signal {
+    let computable = operands.iter().all(|source| match source {
+        Source::Index(input) => matches!(signal(*input), Signal::Value(_)),
+        Source::Value(_) => true,
+    });
+    if !computable {
+        return Signal::Uncomputable;
+    }
+    gate.compute(
+        |i| match operands[i] {
+            Source::Index(input) => match signal(input) {
+                Signal::Value(value) => value,
+                _ => unreachable!(),
+            },
+            Source::Value(value) => value,
+        },
+        |i| widths[i],
+        width,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CircuitBuilder;
+
+    #[test]
+    fn compile_nanocorp_2() -> Result<()> {
+        let mut circuit = Circuit::read("circuits/nanocorp_2.txt")?;
+        let compiled = circuit.compile()?;
+        assert_eq!(compiled.len(), circuit.get_wires().len());
+        assert_eq!(compiled.ids(), circuit.evaluation_order()?);
+
+        let inputs: Vec<u64> = compiled
+            .input_ids()
+            .iter()
+            .map(
+                |id| match circuit.get_wires()[&WireId::new(id.as_str())?].input() {
+                    WireInput::Value(value) => Ok(*value),
+                    _ => unreachable!(),
+                },
+            )
+            .collect::<Result<_>>()?;
+        let mut signals = compiled.signals_buffer();
+        compiled.evaluate_into(&inputs, &mut signals)?;
+
+        circuit.compute_signals()?;
+        for (id, signal) in compiled.ids().iter().zip(&signals) {
+            assert_eq!(circuit.signal(id.as_str()), *signal, "wire {}", id);
+        }
+        let outputs: Vec<Signal> = compiled
+            .output_ids()
+            .iter()
+            .map(|id| circuit.signal(id.as_str()))
+            .collect();
+        assert_eq!(compiled.evaluate(&inputs)?, outputs);
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate() -> Result<()> {
+        let compiled = CircuitBuilder::with_width(8)?
+            .add_wire("0 -> a")?
+            .add_wire("0 -> b:4")?
+            .add_wire("a ADD 1 -> c")?
+            .add_wire("c DIV a -> d")?
+            .add_wire("{b, c} -> e:12")?
+            .add_wire("c -> f")?
+            .add_wire("a AND x -> g")?
+            .add_wire("X -> h")?
+            .add_wire("h OR 1 -> i")?
+            .build()
+            .compile()?;
+        assert_eq!(compiled.input_ids(), ["a", "b"]);
+        assert_eq!(compiled.output_ids(), ["d", "e", "f", "g", "i"]);
+
+        use Signal::{Uncomputable, Value};
+        assert_eq!(
+            compiled.evaluate(&[255, 9])?,
+            [Value(0), Value(0x900), Value(0), Uncomputable, Uncomputable]
+        );
+        assert_eq!(
+            compiled.evaluate(&[0, 15])?,
+            [
+                Uncomputable,
+                Value(0xf01),
+                Value(1),
+                Uncomputable,
+                Uncomputable
+            ]
+        );
+
+        assert!(matches!(
+            compiled.evaluate(&[1]),
+            Err(Error::InputCount(2, 1))
+        ));
+        assert!(matches!(
+            compiled.evaluate(&[1, 16]),
+            Err(Error::TooLargeValue(16))
+        ));
+        assert!(matches!(
+            compiled.evaluate_into(&[1, 1], &mut []),
+            Err(Error::BufferLength(9, 0))
+        ));
+        assert!(matches!(
+            compiled.index_of("z"),
+            Err(Error::UnknownWireId(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn compile_loop() -> Result<()> {
+        let circuit = CircuitBuilder::new()
+            .add_wire("b -> a")?
+            .add_wire("a -> b")?
+            .build();
+        assert!(matches!(circuit.compile(), Err(Error::CircuitLoop(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_batch() -> Result<()> {
+        let mut circuit = CircuitBuilder::with_width(3)?
+            .add_wire("0 -> a")?
+            .add_wire("0 -> b")?
+            .add_wire("a SUB b -> c")?
+            .add_wire("c MOD b -> d")?
+            .add_wire("a LT b -> e:1")?
+            .add_wire("e MUX c d -> f")?
+            .add_wire("{e, f} -> g:4")?
+            .add_wire("g RSHIFT b -> h:4")?
+            .build();
+        let compiled = circuit.compile()?;
+        // More than 64 assignments, so that the lanes span several words of bitmasks
+        let assignments: Vec<Vec<u64>> = (0..130).map(|i| vec![(i >> 3) & 7, i & 7]).collect();
+        let table = compiled.evaluate_batch(&assignments)?;
+        assert_eq!(table.len(), 130);
+
+        for (inputs, outputs) in assignments.iter().zip(&table) {
+            assert_eq!(*outputs, compiled.evaluate(inputs)?);
+            circuit.set_value("a", inputs[0])?;
+            circuit.set_value("b", inputs[1])?;
+            circuit.compute_signals()?;
+            let expected: Vec<Signal> = compiled
+                .output_ids()
+                .iter()
+                .map(|id| circuit.signal(id.as_str()))
+                .collect();
+            assert_eq!(*outputs, expected, "inputs {:?}", inputs);
+        }
+
+        assert!(compiled.evaluate_batch::<[u64; 2]>(&[])?.is_empty());
+        assert!(matches!(
+            compiled.evaluate_batch(&[vec![1, 2], vec![3]]),
+            Err(Error::InputCount(2, 1))
+        ));
+        assert!(matches!(
+            compiled.evaluate_batch(&[[1, 8]]),
+            Err(Error::TooLargeValue(8))
+        ));
+        Ok(())
+    }
+}
@@ -10,19 +10,42 @@ pub enum Error {
     #[error("Wire id '{0}' is not ascii lowercase")]
     InvalidWireId(String),
 
-    /// This value is too large to be represented by type [u16]
-    #[error("Type u16 cannot represent value '{0}'")]
+    /// This value is too large to be represented with the width of the signals
+    #[error("Value '{0}' does not fit in the signal width")]
     TooLargeValue(u64),
 
     /// A wire cannot be an input to itself
     #[error("Identical input and output ids '{0}'")]
     InputMatchesOutput(String),
 
-    /// This shift amount exceeds 15
-    /// (which is the maximum since a signal is represented by type [u16])
-    #[error("Shift amount '{0}' exceeds 15")]
+    /// This shift amount is not less than the width of the signals
+    #[error("Shift amount '{0}' is not less than the signal width")]
     TooLargeShift(u8),
 
+    /// A signal width must be between 1 and 64 bits
+    #[error("Invalid signal width '{0}'")]
+    InvalidWidth(u8),
+
+    /// A division or modulo gate cannot have a zero value as divisor
+    #[error("Division of wire '{0}' by zero")]
+    DivisionByZero(String),
+
+    /// A bit range [msb:lsb] needs lsb <= msb < 64
+    #[error("Invalid bit range [{0}:{1}]")]
+    InvalidBitRange(u8, u8),
+
+    /// A concatenation needs at least one input
+    #[error("Concatenation of no wires")]
+    EmptyConcatenation,
+
+    /// The width declared for a wire cannot be parsed
+    #[error("Cannot parse wire width from string '{0}'")]
+    ParseWidth(String),
+
+    /// The width of a wire does not match that of one of its inputs
+    #[error("Width of wire '{0}' does not match that of its input '{1}'")]
+    WidthMismatch(String, String),
+
     /// The circuit already has a wire with this id
     #[error("Circuit already has a wire whose id is '{0}'")]
     WireIdAlreadyExists(String),
@@ -31,9 +54,37 @@ pub enum Error {
     #[error("Circuit has no wire '{0}'")]
     UnknownWireId(String),
 
-    /// The circuit has a loop
-    #[error("Circuit has a loop")]
-    CircuitLoop,
+    /// The circuit has a loop, going through these wires in the order signals flow
+    #[error("Circuit has a loop: {} -> {}", .0.join(" -> "), .0[0])]
+    CircuitLoop(Vec<String>),
+
+    /// This wire is read by wires of the circuit but is not in it
+    #[error("Wire '{0}' is read but not driven")]
+    UndrivenWire(String),
+
+    /// This wire neither reads nor is read by any other wire
+    #[error("Wire '{0}' is not connected to any other wire")]
+    UnusedWire(String),
+
+    /// The input of this wire does not fit in its width
+    #[error("Wire '{0}' violates its width: {1}")]
+    WidthViolation(String, Box<Error>),
+
+    /// The circuit failed validation with all these problems
+    #[error("Invalid circuit: {}", .0.iter().map(Error::to_string).collect::<Vec<_>>().join("; "))]
+    InvalidCircuit(Vec<Error>),
+
+    /// This string is not the kind of any gate
+    #[error("Unknown gate kind '{0}'")]
+    UnknownGateKind(String),
+
+    /// A compiled circuit was given the wrong number of input values
+    #[error("Expected {0} input values, got {1}")]
+    InputCount(usize, usize),
+
+    /// A compiled circuit was given a signal buffer of the wrong length
+    #[error("Expected a buffer of {0} signals, got {1}")]
+    BufferLength(usize, usize),
 
     /// This string cannot be parsed as a gate
     #[error("Cannot parse string '{0}' as a gate")]
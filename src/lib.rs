@@ -36,14 +36,23 @@
 // Dependency reexports
 pub use thiserror;
 
-pub use circuit::Circuit;
+pub use circuit::{
+    fixed_point::Convergence,
+    stats::CircuitStats,
+    timing::{CriticalPath, PathStep},
+    Circuit,
+};
 pub use circuit_builder::CircuitBuilder;
+pub use compiled_circuit::CompiledCircuit;
 pub use error::Error;
+pub use wire::four_state::FourState;
 pub use wire::signal::Signal;
 
 #[doc(hidden)]
 pub mod circuit;
 #[doc(hidden)]
 pub mod circuit_builder;
+#[doc(hidden)]
+pub mod compiled_circuit;
 pub mod error;
 mod wire;
@@ -1,5 +1,8 @@
+pub mod four_state;
 pub(super) mod gate;
+pub(super) mod operand;
 pub mod signal;
+pub(super) mod width;
 pub(super) mod wire_id;
 pub(super) mod wire_input;
 
@@ -8,6 +11,7 @@ use std::fmt::{self, Display, Formatter};
 use crate::error::{Error, Result};
 use gate::Gate;
 use signal::Signal;
+use width::Width;
 use wire_id::WireId;
 use wire_input::WireInput;
 
@@ -15,14 +19,18 @@ use wire_input::WireInput;
 pub(super) struct Wire {
     id: WireId,
     input: WireInput,
+    width: Option<Width>,
     signal: Signal,
 }
 
 impl Wire {
     fn new(id: WireId, input: WireInput) -> Result<Self> {
         match &input {
-            WireInput::Value(_) => {}
-            WireInput::Wire(input_id) => {
+            WireInput::Value(_) | WireInput::Unknown | WireInput::HighImpedance => {}
+            WireInput::Wire(input_id)
+            | WireInput::Register {
+                input: input_id, ..
+            } => {
                 if &id == input_id {
                     return Err(Error::InputMatchesOutput(id.to_string()));
                 }
@@ -36,14 +44,37 @@ impl Wire {
         Ok(Self {
             id,
             input,
+            width: None,
             signal: Signal::default(),
         })
     }
 
-    pub fn with_value<S: Into<String>>(id: S, value: u16) -> Result<Self> {
+    pub fn with_value<S: Into<String>>(id: S, value: u64) -> Result<Self> {
         Self::new(WireId::new(id)?, WireInput::Value(value))
     }
 
+    pub fn unknown<S: Into<String>>(id: S) -> Result<Self> {
+        Self::new(WireId::new(id)?, WireInput::Unknown)
+    }
+
+    pub fn high_impedance<S: Into<String>>(id: S) -> Result<Self> {
+        Self::new(WireId::new(id)?, WireInput::HighImpedance)
+    }
+
+    pub fn register<S: Into<String>, T: Into<String>>(
+        id: S,
+        input_id: T,
+        initial: u64,
+    ) -> Result<Self> {
+        Self::new(
+            WireId::new(id)?,
+            WireInput::Register {
+                input: WireId::new(input_id)?,
+                initial,
+            },
+        )
+    }
+
     pub fn from_wire<S: Into<String>, T: Into<String>>(id: S, input_id: T) -> Result<Self> {
         Self::new(WireId::new(id)?, WireInput::Wire(WireId::new(input_id)?))
     }
@@ -63,7 +94,7 @@ impl Wire {
     pub fn from_gate_and_value<S: Into<String>, T: Into<String>>(
         id: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<Self> {
         Wire::from_gate(id, Gate::and_value(input, value)?)
     }
@@ -79,11 +110,251 @@ impl Wire {
     pub fn from_gate_or_value<S: Into<String>, T: Into<String>>(
         id: S,
         input: T,
-        value: u16,
+        value: u64,
     ) -> Result<Self> {
         Wire::from_gate(id, Gate::or_value(input, value)?)
     }
 
+    pub fn from_gate_xor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::xor(input1, input2)?)
+    }
+
+    pub fn from_gate_xor_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::xor_value(input, value)?)
+    }
+
+    pub fn from_gate_nand<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::nand(input1, input2)?)
+    }
+
+    pub fn from_gate_nand_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::nand_value(input, value)?)
+    }
+
+    pub fn from_gate_nor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::nor(input1, input2)?)
+    }
+
+    pub fn from_gate_nor_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::nor_value(input, value)?)
+    }
+
+    pub fn from_gate_xnor<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::xnor(input1, input2)?)
+    }
+
+    pub fn from_gate_xnor_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::xnor_value(input, value)?)
+    }
+
+    pub fn from_gate_add<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::add(input1, input2)?)
+    }
+
+    pub fn from_gate_add_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::add_value(input, value)?)
+    }
+
+    pub fn from_gate_sub<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::sub(input1, input2)?)
+    }
+
+    pub fn from_gate_sub_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::sub_value(input, value)?)
+    }
+
+    pub fn from_gate_mul<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::mul(input1, input2)?)
+    }
+
+    pub fn from_gate_mul_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::mul_value(input, value)?)
+    }
+
+    pub fn from_gate_div<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::div(input1, input2)?)
+    }
+
+    pub fn from_gate_div_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::div_value(input, value)?)
+    }
+
+    pub fn from_gate_mod<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::r#mod(input1, input2)?)
+    }
+
+    pub fn from_gate_mod_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::mod_value(input, value)?)
+    }
+
+    pub fn from_gate_eq<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::eq(input1, input2)?)
+    }
+
+    pub fn from_gate_eq_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::eq_value(input, value)?)
+    }
+
+    pub fn from_gate_ne<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::ne(input1, input2)?)
+    }
+
+    pub fn from_gate_ne_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::ne_value(input, value)?)
+    }
+
+    pub fn from_gate_lt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::lt(input1, input2)?)
+    }
+
+    pub fn from_gate_lt_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::lt_value(input, value)?)
+    }
+
+    pub fn from_gate_le<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::le(input1, input2)?)
+    }
+
+    pub fn from_gate_le_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::le_value(input, value)?)
+    }
+
+    pub fn from_gate_gt<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::gt(input1, input2)?)
+    }
+
+    pub fn from_gate_gt_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::gt_value(input, value)?)
+    }
+
+    pub fn from_gate_ge<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::ge(input1, input2)?)
+    }
+
+    pub fn from_gate_ge_value<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        value: u64,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::ge_value(input, value)?)
+    }
+
     pub fn from_gate_lshift<S: Into<String>, T: Into<String>>(
         id: S,
         input: T,
@@ -100,10 +371,210 @@ impl Wire {
         Wire::from_gate(id, Gate::rshift(input, shift)?)
     }
 
+    pub fn from_gate_lshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input: T,
+        shift: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::lshift_wire(input, shift)?)
+    }
+
+    pub fn from_gate_rshift_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input: T,
+        shift: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::rshift_wire(input, shift)?)
+    }
+
+    pub fn from_gate_rotl<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        shift: u8,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::rotl(input, shift)?)
+    }
+
+    pub fn from_gate_rotl_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input: T,
+        shift: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::rotl_wire(input, shift)?)
+    }
+
+    pub fn from_gate_rotr<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        shift: u8,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::rotr(input, shift)?)
+    }
+
+    pub fn from_gate_rotr_wire<S: Into<String>, T: Into<String>, U: Into<String>>(
+        id: S,
+        input: T,
+        shift: U,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::rotr_wire(input, shift)?)
+    }
+
     pub fn from_gate_not<S: Into<String>, T: Into<String>>(id: S, input: T) -> Result<Self> {
         Wire::from_gate(id, Gate::not(input)?)
     }
 
+    pub fn from_gate_slice<S: Into<String>, T: Into<String>>(
+        id: S,
+        input: T,
+        msb: u8,
+        lsb: u8,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::slice(input, msb, lsb)?)
+    }
+
+    pub fn from_gate_concat<S: Into<String>, I: IntoIterator<Item = T>, T: Into<String>>(
+        id: S,
+        inputs: I,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::concat(inputs)?)
+    }
+
+    pub fn from_gate_mux<S: Into<String>, T: Into<String>, U: Into<String>, V: Into<String>>(
+        id: S,
+        select: T,
+        input1: U,
+        input2: V,
+    ) -> Result<Self> {
+        Wire::from_gate(id, Gate::mux(select, input1, input2)?)
+    }
+
+    // Parses the string representation of a wire without checking its width
+    // (see check_width())
+    pub(super) fn parse(s: &str) -> Result<Self> {
+        let (input, output) = s
+            .split_once(" -> ")
+            .ok_or(Error::ParseArrow(s.to_string()))?;
+        let (output, width) = Wire::parse_output(output)?;
+        let inputs: Vec<&str> = input.split(' ').collect();
+        let mut wire = match inputs.len() {
+            2 | 4 if inputs[0] == "REG" => match inputs[..] {
+                [_, input_id] => Wire::register(output, input_id, 0),
+                [_, input_id, "INIT", initial] => {
+                    let initial = initial
+                        .parse::<u64>()
+                        .map_err(|_| Error::ParseGate(input.to_string()))?;
+                    Wire::register(output, input_id, initial)
+                }
+                _ => Err(Error::ParseGate(input.to_string())),
+            },
+            1 if !input.ends_with([']', '}']) => {
+                if let Ok(value) = inputs[0].parse::<u64>() {
+                    Wire::with_value(output, value)
+                } else if input == "X" {
+                    Wire::unknown(output)
+                } else if input == "Z" {
+                    Wire::high_impedance(output)
+                } else {
+                    Wire::from_wire(output, inputs[0])
+                }
+            }
+            _ => Wire::from_gate(output, Gate::try_from(input)?),
+        }?;
+        wire.width = width;
+        Ok(wire)
+    }
+
+    // Helper function of parse()
+    // Splits the output of a wire ("id", "id:width" or "[msb:lsb] id")
+    // into its id and its declared width
+    fn parse_output(s: &str) -> Result<(&str, Option<Width>)> {
+        let parse_bits = |bits: &str| {
+            bits.parse::<u8>()
+                .map_err(|_| Error::ParseWidth(s.to_string()))
+        };
+        if let Some(rest) = s.strip_prefix('[') {
+            let (range, id) = rest
+                .split_once("] ")
+                .ok_or(Error::ParseWidth(s.to_string()))?;
+            let (msb, lsb) = range
+                .split_once(':')
+                .ok_or(Error::ParseWidth(s.to_string()))?;
+            let bits = parse_bits(msb)?
+                .checked_sub(parse_bits(lsb)?)
+                .and_then(|bits| bits.checked_add(1))
+                .ok_or(Error::ParseWidth(s.to_string()))?;
+            Ok((id, Some(Width::new(bits)?)))
+        } else if let Some((id, bits)) = s.split_once(':') {
+            Ok((id, Some(Width::new(parse_bits(bits)?)?)))
+        } else {
+            Ok((s, None))
+        }
+    }
+
+    // Checks that the values and shift amounts of the wire input fit in `width` bits
+    pub(super) fn check_width(&self, width: Width) -> Result<()> {
+        match &self.input {
+            WireInput::Value(value) | WireInput::Register { initial: value, .. } => {
+                width.check_value(*value)
+            }
+            WireInput::Unknown | WireInput::HighImpedance | WireInput::Wire(_) => Ok(()),
+            WireInput::Gate(gate) => gate.check_width(width),
+        }
+    }
+
+    // Checks that the widths of the inputs of the wire are consistent with its own.
+    // `default` is the width of wires with no declared width
+    // and `width_of` returns the width of a wire if it exists.
+    pub(super) fn check_input_widths(
+        &self,
+        default: Width,
+        width_of: &dyn Fn(&WireId) -> Option<Width>,
+    ) -> Result<()> {
+        let width = self.width_or(default);
+        let mismatch = match &self.input {
+            WireInput::Value(_) | WireInput::Unknown | WireInput::HighImpedance => None,
+            WireInput::Wire(input_id)
+            | WireInput::Register {
+                input: input_id, ..
+            } => Some(input_id).filter(|id| width_of(id).is_some_and(|w| w != width)),
+            WireInput::Gate(gate) => gate.mismatched_input(width, width_of),
+        };
+        match mismatch {
+            Some(input_id) => Err(Error::WidthMismatch(
+                self.id.to_string(),
+                input_id.to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    // Returns the wires feeding the wire, in order
+    pub(super) fn inputs(&self) -> Vec<&WireId> {
+        match &self.input {
+            WireInput::Value(_) | WireInput::Unknown | WireInput::HighImpedance => vec![],
+            WireInput::Wire(input_id)
+            | WireInput::Register {
+                input: input_id, ..
+            } => {
+                vec![input_id]
+            }
+            WireInput::Gate(gate) => gate.inputs(),
+        }
+    }
+
+    // Returns the wires whose signals the signal of the wire depends on within a clock cycle,
+    // that is its inputs unless it is a register
+    pub(super) fn combinational_inputs(&self) -> Vec<&WireId> {
+        match &self.input {
+            WireInput::Register { .. } => vec![],
+            _ => self.inputs(),
+        }
+    }
+
+    pub(super) fn is_register(&self) -> bool {
+        matches!(self.input, WireInput::Register { .. })
+    }
+
     pub(super) fn id(&self) -> &WireId {
         &self.id
     }
@@ -112,6 +583,19 @@ impl Wire {
         &self.input
     }
 
+    pub(super) fn width(&self) -> Option<Width> {
+        self.width
+    }
+
+    // Returns the declared width of the wire or `default` if it has none
+    pub(super) fn width_or(&self, default: Width) -> Width {
+        self.width.unwrap_or(default)
+    }
+
+    pub(super) fn set_width(&mut self, width: Option<Width>) {
+        self.width = width;
+    }
+
     pub fn signal(&self) -> &Signal {
         &self.signal
     }
@@ -125,22 +609,9 @@ impl TryFrom<&str> for Wire {
     type Error = Error;
 
     fn try_from(s: &str) -> Result<Self> {
-        let (input, output) = s
-            .split_once(" -> ")
-            .ok_or(Error::ParseArrow(s.to_string()))?;
-        let inputs: Vec<&str> = input.split(' ').collect();
-        match inputs.len() {
-            1 => {
-                if let Ok(value) = inputs[0].parse::<u16>() {
-                    Wire::with_value(output, value)
-                } else if let Ok(value) = inputs[0].parse::<u64>() {
-                    Err(Error::TooLargeValue(value))
-                } else {
-                    Wire::from_wire(output, inputs[0])
-                }
-            }
-            _ => Wire::from_gate(output, Gate::try_from(input)?),
-        }
+        let wire = Wire::parse(s)?;
+        wire.check_width(wire.width_or(Width::default()))?;
+        Ok(wire)
     }
 }
 
@@ -148,15 +619,31 @@ impl Display for Wire {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self.input {
             WireInput::Value(value) => {
-                write!(f, "{} -> {}", value, self.id)
+                write!(f, "{} -> {}", value, self.id)?;
+            }
+            WireInput::Unknown => {
+                write!(f, "X -> {}", self.id)?;
+            }
+            WireInput::HighImpedance => {
+                write!(f, "Z -> {}", self.id)?;
             }
             WireInput::Wire(input_id) => {
-                write!(f, "{} -> {}", input_id, self.id)
+                write!(f, "{} -> {}", input_id, self.id)?;
             }
             WireInput::Gate(gate) => {
-                write!(f, "{} -> {}", gate, self.id)
+                write!(f, "{} -> {}", gate, self.id)?;
+            }
+            WireInput::Register { input, initial: 0 } => {
+                write!(f, "REG {} -> {}", input, self.id)?;
+            }
+            WireInput::Register { input, initial } => {
+                write!(f, "REG {} INIT {} -> {}", input, initial, self.id)?;
             }
         }
+        match self.width {
+            Some(width) => write!(f, ":{}", width),
+            None => Ok(()),
+        }
     }
 }
 
@@ -236,8 +723,8 @@ mod tests {
         assert!(Wire::try_from("a RSHIFT 15 -> w").is_ok());
 
         assert!(matches!(
-            Wire::from_gate_rshift("rshift", "w", 16),
-            Err(Error::TooLargeShift(16))
+            Wire::from_gate_rshift("rshift", "w", 64),
+            Err(Error::TooLargeShift(64))
         ));
         assert!(matches!(
             Wire::try_from("a LSHIFT 16 -> w"),
@@ -263,8 +750,9 @@ mod tests {
 
     #[test]
     fn parse_shift() {
+        assert!(Wire::try_from("a RSHIFT b -> w").is_ok());
         assert!(matches!(
-            Wire::try_from("a RSHIFT b -> w"),
+            Wire::try_from("a RSHIFT B -> w"),
             Err(Error::ParseShift(_))
         ));
         assert!(matches!(
@@ -273,6 +761,87 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn parse_register() -> Result<()> {
+        assert_eq!(
+            Wire::try_from("REG d -> q")?.input,
+            WireInput::Register {
+                input: WireId::new("d")?,
+                initial: 0
+            }
+        );
+        assert_eq!(
+            Wire::try_from("REG d INIT 5 -> q:4")?.to_string(),
+            "REG d INIT 5 -> q:4"
+        );
+        assert_eq!(
+            Wire::try_from("REG d INIT 0 -> q")?.to_string(),
+            "REG d -> q"
+        );
+        assert!(matches!(
+            Wire::try_from("REG d INIT 16 -> q:4"),
+            Err(Error::TooLargeValue(16))
+        ));
+        assert!(matches!(
+            Wire::try_from("REG d INIT x -> q"),
+            Err(Error::ParseGate(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("REG d 5 -> q"),
+            Err(Error::ParseGate(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("REG q -> q"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_width() -> Result<()> {
+        assert_eq!(Wire::try_from("255 -> x:8")?.width, Some(Width::new(8)?));
+        assert_eq!(Wire::try_from("y -> [7:0] x")?.width, Some(Width::new(8)?));
+        assert_eq!(Wire::try_from("y -> [4:1] x")?.width, Some(Width::new(4)?));
+        assert_eq!(Wire::try_from("y -> x")?.width, None);
+        assert_eq!(Wire::try_from("y -> x:8")?.to_string(), "y -> x:8");
+        assert_eq!(Wire::try_from("y -> [63:0] x")?.to_string(), "y -> x:64");
+
+        assert!(matches!(
+            Wire::try_from("256 -> x:8"),
+            Err(Error::TooLargeValue(256))
+        ));
+        assert!(matches!(
+            Wire::try_from("y ROTL 8 -> x:8"),
+            Err(Error::TooLargeShift(8))
+        ));
+        assert!(Wire::try_from("65536 -> x:17").is_ok());
+        assert!(matches!(
+            Wire::try_from("y -> x:"),
+            Err(Error::ParseWidth(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("y -> [0:7] x"),
+            Err(Error::ParseWidth(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("y -> [7:0]x"),
+            Err(Error::ParseWidth(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("y -> x:0"),
+            Err(Error::InvalidWidth(0))
+        ));
+        assert!(matches!(
+            Wire::try_from("y -> [64:0] x"),
+            Err(Error::InvalidWidth(65))
+        ));
+        assert!(matches!(
+            Wire::try_from("y -> x:8:8"),
+            Err(Error::ParseWidth(_))
+        ));
+        Ok(())
+    }
+
     #[test]
     fn parse_arrow() {
         assert!(matches!(Wire::try_from(""), Err(Error::ParseArrow(_))));
@@ -311,6 +880,14 @@ mod tests {
             Wire::try_from("1 OR w -> w"),
             Err(Error::InputMatchesOutput(_))
         ));
+        assert!(matches!(
+            Wire::from_gate_xor("w", "x", "w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("w NAND 1 -> w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
         assert!(matches!(
             Wire::from_gate_lshift("w", "w", 1),
             Err(Error::InputMatchesOutput(_))
@@ -319,6 +896,30 @@ mod tests {
             Wire::from_gate_rshift("w", "w", 1),
             Err(Error::InputMatchesOutput(_))
         ));
+        assert!(matches!(
+            Wire::try_from("x LSHIFT w -> w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::from_gate_rotr_wire("w", "w", "x"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::from_gate_mux("w", "s", "x", "w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("w MUX x y -> w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("w[3:0] -> w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
+        assert!(matches!(
+            Wire::try_from("{x, w} -> w"),
+            Err(Error::InputMatchesOutput(_))
+        ));
         assert!(matches!(
             Wire::from_gate_not("w", "w"),
             Err(Error::InputMatchesOutput(_))
@@ -333,6 +934,16 @@ mod tests {
         assert_eq!(w1.input, w2.input);
         assert_eq!(w1.signal, w2.signal);
 
+        let w1 = Wire::try_from("X -> y")?;
+        let w2 = Wire::unknown("y")?;
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.to_string(), "X -> y");
+
+        let w1 = Wire::try_from("Z -> y:4")?;
+        let w2 = Wire::high_impedance("y")?;
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.to_string(), "Z -> y:4");
+
         let w1 = Wire::try_from("x LSHIFT 2 -> f")?;
         let w2 = Wire::from_gate_lshift("f", "x", 2)?;
         assert_eq!(w1.id, w2.id);
@@ -374,6 +985,36 @@ mod tests {
         assert_eq!(w1.id, w2.id);
         assert_eq!(w1.input, w2.input);
         assert_eq!(w1.signal, w2.signal);
+
+        let w1 = Wire::try_from("s MUX x y -> m")?;
+        let w2 = Wire::from_gate_mux("m", "s", "x", "y")?;
+        assert_eq!(w1.id, w2.id);
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.signal, w2.signal);
+
+        let w1 = Wire::try_from("x[7:4] -> n")?;
+        let w2 = Wire::from_gate_slice("n", "x", 7, 4)?;
+        assert_eq!(w1.id, w2.id);
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.signal, w2.signal);
+
+        let w1 = Wire::try_from("{x, y} -> o")?;
+        let w2 = Wire::from_gate_concat("o", ["x", "y"])?;
+        assert_eq!(w1.id, w2.id);
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.signal, w2.signal);
+
+        let w1 = Wire::try_from("x XOR y -> j")?;
+        let w2 = Wire::from_gate_xor("j", "x", "y")?;
+        assert_eq!(w1.id, w2.id);
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.signal, w2.signal);
+
+        let w1 = Wire::try_from("x XNOR 7 -> k")?;
+        let w2 = Wire::from_gate_xnor_value("k", "x", 7)?;
+        assert_eq!(w1.id, w2.id);
+        assert_eq!(w1.input, w2.input);
+        assert_eq!(w1.signal, w2.signal);
         Ok(())
     }
 }
@@ -0,0 +1,330 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::width::Width;
+
+/// A signal whose bits each are 0, 1, unknown (X) or high-impedance (Z).
+///
+/// See [here](crate::Circuit::four_state_signals) for more details.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FourState {
+    width: Width,
+    value: u64,
+    unknown: u64,
+    high_impedance: u64,
+}
+
+impl FourState {
+    pub(crate) fn known(value: u64, width: Width) -> Self {
+        Self {
+            width,
+            value: value & width.mask(),
+            unknown: 0,
+            high_impedance: 0,
+        }
+    }
+
+    pub(crate) fn unknown(width: Width) -> Self {
+        Self {
+            width,
+            value: 0,
+            unknown: width.mask(),
+            high_impedance: 0,
+        }
+    }
+
+    pub(crate) fn high_impedance(width: Width) -> Self {
+        Self {
+            width,
+            value: 0,
+            unknown: 0,
+            high_impedance: width.mask(),
+        }
+    }
+
+    /// Returns the width in bits of the signal.
+    pub fn width(&self) -> u8 {
+        self.width.bits()
+    }
+
+    /// Returns the value of the signal if all its bits are 0 or 1.
+    pub fn value(&self) -> Option<u64> {
+        if self.undefined() == 0 {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mask of the bits which are 0 or 1.
+    pub fn known_bits(&self) -> u64 {
+        !self.undefined() & self.width.mask()
+    }
+
+    /// Returns the mask of the unknown (X) bits.
+    pub fn unknown_bits(&self) -> u64 {
+        self.unknown
+    }
+
+    /// Returns the mask of the high-impedance (Z) bits.
+    pub fn high_impedance_bits(&self) -> u64 {
+        self.high_impedance
+    }
+
+    pub(crate) fn bit_width(&self) -> Width {
+        self.width
+    }
+
+    // Bits which are X or Z (gates treat Z inputs as X)
+    fn undefined(&self) -> u64 {
+        self.unknown | self.high_impedance
+    }
+
+    // Bits known to be 1
+    pub(crate) fn ones(&self) -> u64 {
+        self.value & self.known_bits()
+    }
+
+    // Bits known to be 0
+    fn zeros(&self) -> u64 {
+        !self.value & !self.undefined() & self.width.mask()
+    }
+
+    // Builds a signal from its known ones and known zeros, all other bits being X
+    fn from_ones_and_zeros(width: Width, ones: u64, zeros: u64) -> Self {
+        Self {
+            width,
+            value: ones & width.mask(),
+            unknown: !(ones | zeros) & width.mask(),
+            high_impedance: 0,
+        }
+    }
+
+    /// Returns the signal truncated or zero-extended to `width` bits.
+    pub(crate) fn resize(&self, width: Width) -> Self {
+        Self {
+            width,
+            value: self.value & width.mask(),
+            unknown: self.unknown & width.mask(),
+            high_impedance: self.high_impedance & width.mask(),
+        }
+    }
+
+    pub(crate) fn and(&self, other: &Self) -> Self {
+        Self::from_ones_and_zeros(
+            self.width,
+            self.value & other.value,
+            self.zeros() | other.zeros(),
+        )
+    }
+
+    pub(crate) fn or(&self, other: &Self) -> Self {
+        Self::from_ones_and_zeros(
+            self.width,
+            self.value | other.value,
+            self.zeros() & other.zeros(),
+        )
+    }
+
+    pub(crate) fn xor(&self, other: &Self) -> Self {
+        let unknown = self.undefined() | other.undefined();
+        let value = (self.value ^ other.value) & !unknown;
+        Self::from_ones_and_zeros(self.width, value, !value & !unknown)
+    }
+
+    pub(crate) fn not(&self) -> Self {
+        Self::from_ones_and_zeros(self.width, self.zeros(), self.value)
+    }
+
+    /// Applies `f` to the bits of `self` and `other` which lie below their lowest undefined bit,
+    /// all bits from there up being X.
+    /// This is exact for operations like addition whose low bits only depend on low bits.
+    pub(crate) fn below_undefined<F: Fn(u64, u64) -> u64>(&self, other: &Self, f: F) -> Self {
+        let undefined = self.undefined() | other.undefined();
+        let known = 1u64
+            .checked_shl(undefined.trailing_zeros())
+            .map_or(u64::MAX, |bit| bit - 1);
+        let value = f(self.value, other.value) & known;
+        Self::from_ones_and_zeros(self.width, value, !value & known)
+    }
+
+    /// Applies `f` to each of the value, X and Z masks of the signal, yielding a signal
+    /// of `width` bits. Bits brought in by `f` (e.g. by a shift) are 0.
+    pub(crate) fn map_bits<F: Fn(u64) -> u64>(&self, width: Width, f: F) -> Self {
+        Self {
+            width,
+            value: f(self.value) & width.mask(),
+            unknown: f(self.unknown) & width.mask(),
+            high_impedance: f(self.high_impedance) & width.mask(),
+        }
+    }
+
+    /// Concatenates `self` (most significant bits) with `other`.
+    pub(crate) fn concat(&self, other: &Self, width: Width) -> Self {
+        let shift = u32::from(other.width.bits());
+        let join = |high: u64, low: u64| high.checked_shl(shift).unwrap_or(0) | low;
+        Self {
+            width,
+            value: join(self.value, other.value) & width.mask(),
+            unknown: join(self.unknown, other.unknown) & width.mask(),
+            high_impedance: join(self.high_impedance, other.high_impedance) & width.mask(),
+        }
+    }
+
+    /// Keeps the bits on which `self` and `other` agree, all other bits being X.
+    pub(crate) fn merge(&self, other: &Self) -> Self {
+        let known = !self.undefined() & !other.undefined() & !(self.value ^ other.value);
+        Self::from_ones_and_zeros(self.width, self.value & known, !self.value & known)
+    }
+
+    /// Returns 1 if both signals are equal, 0 if they differ on a known bit and X otherwise.
+    pub(crate) fn equals(&self, other: &Self, width: Width) -> Self {
+        let known = !self.undefined() & !other.undefined();
+        if (self.value ^ other.value) & known != 0 {
+            Self::known(0, width)
+        } else if self.undefined() | other.undefined() != 0 {
+            Self::unknown_lsb(width)
+        } else {
+            Self::known(1, width)
+        }
+    }
+
+    /// Returns 1 if `self` is less than `other` whatever their X and Z bits,
+    /// 0 if it cannot be and X otherwise.
+    /// Comparing the smallest and largest values the signals can take amounts to comparing
+    /// their bits from the most significant one down to the first which is not known
+    /// in both signals.
+    pub(crate) fn less_than(&self, other: &Self, width: Width) -> Self {
+        let (min, max) = self.range();
+        let (other_min, other_max) = other.range();
+        if max < other_min {
+            Self::known(1, width)
+        } else if min >= other_max {
+            Self::known(0, width)
+        } else {
+            Self::unknown_lsb(width)
+        }
+    }
+
+    // Returns the smallest and largest values of the signal, X and Z bits being 0 or 1
+    fn range(&self) -> (u64, u64) {
+        let undefined = self.undefined();
+        (self.value & !undefined, self.value | undefined)
+    }
+
+    /// Returns the signal with its Z bits turned into X bits.
+    pub(crate) fn z_as_x(&self) -> Self {
+        Self {
+            unknown: self.undefined(),
+            high_impedance: 0,
+            ..*self
+        }
+    }
+
+    /// Returns a signal whose least significant bit is X and all other bits 0.
+    pub(crate) fn unknown_lsb(width: Width) -> Self {
+        Self::from_ones_and_zeros(width, 0, width.mask() & !1)
+    }
+}
+
+impl Display for FourState {
+    /// Prints the bits of the signal from the most significant one
+    /// as characters '0', '1', 'x' and 'z'.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for bit in (0..self.width.bits()).rev() {
+            let mask = 1u64 << bit;
+            let c = if self.unknown & mask != 0 {
+                'x'
+            } else if self.high_impedance & mask != 0 {
+                'z'
+            } else if self.value & mask != 0 {
+                '1'
+            } else {
+                '0'
+            };
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+
+    fn parse(s: &str) -> FourState {
+        let width = Width::new(s.len() as u8).unwrap();
+        let mask = |c: char| s.chars().fold(0, |bits, b| (bits << 1) | u64::from(b == c));
+        FourState {
+            width,
+            value: mask('1'),
+            unknown: mask('x'),
+            high_impedance: mask('z'),
+        }
+    }
+
+    #[test]
+    fn display() -> Result<()> {
+        assert_eq!(FourState::known(5, Width::new(4)?).to_string(), "0101");
+        assert_eq!(FourState::unknown(Width::new(3)?).to_string(), "xxx");
+        assert_eq!(FourState::high_impedance(Width::new(2)?).to_string(), "zz");
+        assert_eq!(parse("01xz").to_string(), "01xz");
+        Ok(())
+    }
+
+    #[test]
+    fn logic() {
+        assert_eq!(parse("01xz").and(&parse("0000")), parse("0000"));
+        assert_eq!(parse("01xz").and(&parse("1111")), parse("01xx"));
+        assert_eq!(parse("01xz").or(&parse("1111")), parse("1111"));
+        assert_eq!(parse("01xz").or(&parse("0000")), parse("01xx"));
+        assert_eq!(parse("01xz").xor(&parse("1100")), parse("10xx"));
+        assert_eq!(parse("01xz").not(), parse("10xx"));
+        assert_eq!(parse("01xz").merge(&parse("0111")), parse("01xx"));
+        assert_eq!(parse("0x").value(), None);
+        assert_eq!(parse("01").value(), Some(1));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let add = |a: u64, b: u64| a.wrapping_add(b);
+        assert_eq!(
+            parse("0x01").below_undefined(&parse("0001"), add),
+            parse("xx10")
+        );
+        assert_eq!(
+            parse("x000").below_undefined(&parse("0z11"), add),
+            parse("xx11")
+        );
+        assert_eq!(
+            parse("0011").below_undefined(&parse("0001"), add),
+            parse("0100")
+        );
+    }
+
+    #[test]
+    fn comparison() -> Result<()> {
+        let w = Width::new(4)?;
+        assert_eq!(parse("01x1").equals(&parse("0000"), w), parse("0000"));
+        assert_eq!(parse("01x1").equals(&parse("0101"), w), parse("000x"));
+        assert_eq!(parse("0101").equals(&parse("0101"), w), parse("0001"));
+        assert_eq!(parse("01xx").less_than(&parse("1xz0"), w), parse("0001"));
+        assert_eq!(parse("1x00").less_than(&parse("01xz"), w), parse("0000"));
+        assert_eq!(parse("0x00").less_than(&parse("0100"), w), parse("000x"));
+        assert_eq!(parse("0100").less_than(&parse("0100"), w), parse("0000"));
+        assert_eq!(parse("z1x0").z_as_x(), parse("x1x0"));
+        Ok(())
+    }
+
+    #[test]
+    fn bit_moves() -> Result<()> {
+        let w = Width::new(4)?;
+        assert_eq!(parse("z1x0").map_bits(w, |m| w.shl(m, 1)), parse("1x00"));
+        assert_eq!(parse("z1x0").map_bits(w, |m| w.rotr(m, 1)), parse("0z1x"));
+        assert_eq!(
+            parse("z1").concat(&parse("x0"), Width::new(5)?),
+            parse("0z1x0")
+        );
+        Ok(())
+    }
+}
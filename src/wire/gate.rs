@@ -1,94 +1,1047 @@
 use std::fmt::{self, Display, Formatter};
 
-use super::{signal::Signal, wire_id::WireId};
+use super::{
+    four_state::FourState, operand::Operand, signal::Signal, width::Width, wire_id::WireId,
+};
 use crate::error::{Error, Result};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub(crate) enum Gate {
-    And { input1: WireId, input2: WireId },
-    AndValue { input: WireId, value: u16 },
-    Or { input1: WireId, input2: WireId },
-    OrValue { input: WireId, value: u16 },
-    LShift { input: WireId, shift: u8 },
-    RShift { input: WireId, shift: u8 },
-    Not { input: WireId },
+    And {
+        input1: Operand,
+        input2: Operand,
+    },
+    Or {
+        input1: Operand,
+        input2: Operand,
+    },
+    Xor {
+        input1: Operand,
+        input2: Operand,
+    },
+    Nand {
+        input1: Operand,
+        input2: Operand,
+    },
+    Nor {
+        input1: Operand,
+        input2: Operand,
+    },
+    Xnor {
+        input1: Operand,
+        input2: Operand,
+    },
+    Add {
+        input1: Operand,
+        input2: Operand,
+    },
+    Sub {
+        input1: Operand,
+        input2: Operand,
+    },
+    Mul {
+        input1: Operand,
+        input2: Operand,
+    },
+    Div {
+        input1: Operand,
+        input2: Operand,
+    },
+    Mod {
+        input1: Operand,
+        input2: Operand,
+    },
+    Eq {
+        input1: Operand,
+        input2: Operand,
+    },
+    Ne {
+        input1: Operand,
+        input2: Operand,
+    },
+    Lt {
+        input1: Operand,
+        input2: Operand,
+    },
+    Le {
+        input1: Operand,
+        input2: Operand,
+    },
+    Gt {
+        input1: Operand,
+        input2: Operand,
+    },
+    Ge {
+        input1: Operand,
+        input2: Operand,
+    },
+    LShift {
+        input: Operand,
+        shift: Operand,
+    },
+    RShift {
+        input: Operand,
+        shift: Operand,
+    },
+    RotL {
+        input: Operand,
+        shift: Operand,
+    },
+    RotR {
+        input: Operand,
+        shift: Operand,
+    },
+    Not {
+        input: Operand,
+    },
+    Mux {
+        select: Operand,
+        input1: Operand,
+        input2: Operand,
+    },
+    Slice {
+        input: Operand,
+        msb: u8,
+        lsb: u8,
+    },
+    Concat {
+        inputs: Vec<Operand>,
+    },
 }
 
 impl Gate {
     pub fn and<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
         Ok(Self::And {
-            input1: WireId::new(input1)?,
-            input2: WireId::new(input2)?,
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
         })
     }
 
-    pub fn and_value<S: Into<String>>(input: S, value: u16) -> Result<Self> {
-        Ok(Self::AndValue {
-            input: WireId::new(input)?,
-            value,
+    pub fn and_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::And {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
         })
     }
 
     pub fn or<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
         Ok(Self::Or {
-            input1: WireId::new(input1)?,
-            input2: WireId::new(input2)?,
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn or_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Or {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn xor<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Xor {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn xor_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Xor {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn nand<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Nand {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn nand_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Nand {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn nor<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Nor {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn nor_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Nor {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn xnor<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Xnor {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn xnor_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Xnor {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn add<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Add {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn add_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Add {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn sub<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Sub {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn sub_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Sub {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn mul<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Mul {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn mul_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Mul {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn div<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Div {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn div_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Self::Div {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        }
+        .validated()
+    }
+
+    pub fn r#mod<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Mod {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn mod_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Self::Mod {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        }
+        .validated()
+    }
+
+    pub fn eq<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Eq {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn eq_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Eq {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn ne<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Ne {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn ne_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Ne {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn lt<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Lt {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn lt_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Lt {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn le<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Le {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn le_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Le {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn gt<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Gt {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
+        })
+    }
+
+    pub fn gt_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Gt {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
+        })
+    }
+
+    pub fn ge<S: Into<String>, T: Into<String>>(input1: S, input2: T) -> Result<Self> {
+        Ok(Self::Ge {
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
         })
     }
 
-    pub fn or_value<S: Into<String>>(input: S, value: u16) -> Result<Self> {
-        Ok(Self::OrValue {
-            input: WireId::new(input)?,
-            value,
+    pub fn ge_value<S: Into<String>>(input: S, value: u64) -> Result<Self> {
+        Ok(Self::Ge {
+            input1: Operand::wire(input)?,
+            input2: Operand::Value(value),
         })
     }
 
     pub fn lshift<S: Into<String>>(input: S, shift: u8) -> Result<Self> {
-        let input = WireId::new(input)?;
-        if shift < 16 {
-            Ok(Self::LShift { input, shift })
-        } else {
-            Err(Error::TooLargeShift(shift))
+        Self::LShift {
+            input: Operand::wire(input)?,
+            shift: Operand::Value(u64::from(shift)),
         }
+        .validated()
+    }
+
+    pub fn lshift_wire<S: Into<String>, T: Into<String>>(input: S, shift: T) -> Result<Self> {
+        Ok(Self::LShift {
+            input: Operand::wire(input)?,
+            shift: Operand::wire(shift)?,
+        })
     }
 
     pub fn rshift<S: Into<String>>(input: S, shift: u8) -> Result<Self> {
-        let input = WireId::new(input)?;
-        if shift < 16 {
-            Ok(Self::RShift { input, shift })
-        } else {
-            Err(Error::TooLargeShift(shift))
+        Self::RShift {
+            input: Operand::wire(input)?,
+            shift: Operand::Value(u64::from(shift)),
+        }
+        .validated()
+    }
+
+    pub fn rshift_wire<S: Into<String>, T: Into<String>>(input: S, shift: T) -> Result<Self> {
+        Ok(Self::RShift {
+            input: Operand::wire(input)?,
+            shift: Operand::wire(shift)?,
+        })
+    }
+
+    pub fn rotl<S: Into<String>>(input: S, shift: u8) -> Result<Self> {
+        Self::RotL {
+            input: Operand::wire(input)?,
+            shift: Operand::Value(u64::from(shift)),
+        }
+        .validated()
+    }
+
+    pub fn rotl_wire<S: Into<String>, T: Into<String>>(input: S, shift: T) -> Result<Self> {
+        Ok(Self::RotL {
+            input: Operand::wire(input)?,
+            shift: Operand::wire(shift)?,
+        })
+    }
+
+    pub fn rotr<S: Into<String>>(input: S, shift: u8) -> Result<Self> {
+        Self::RotR {
+            input: Operand::wire(input)?,
+            shift: Operand::Value(u64::from(shift)),
         }
+        .validated()
+    }
+
+    pub fn rotr_wire<S: Into<String>, T: Into<String>>(input: S, shift: T) -> Result<Self> {
+        Ok(Self::RotR {
+            input: Operand::wire(input)?,
+            shift: Operand::wire(shift)?,
+        })
     }
 
     pub fn not<S: Into<String>>(input: S) -> Result<Self> {
         Ok(Self::Not {
-            input: WireId::new(input)?,
+            input: Operand::wire(input)?,
+        })
+    }
+
+    pub fn mux<S: Into<String>, T: Into<String>, U: Into<String>>(
+        select: S,
+        input1: T,
+        input2: U,
+    ) -> Result<Self> {
+        Ok(Self::Mux {
+            select: Operand::wire(select)?,
+            input1: Operand::wire(input1)?,
+            input2: Operand::wire(input2)?,
         })
     }
 
+    pub fn slice<S: Into<String>>(input: S, msb: u8, lsb: u8) -> Result<Self> {
+        Self::Slice {
+            input: Operand::wire(input)?,
+            msb,
+            lsb,
+        }
+        .validated()
+    }
+
+    pub fn concat<I: IntoIterator<Item = S>, S: Into<String>>(inputs: I) -> Result<Self> {
+        let inputs = inputs
+            .into_iter()
+            .map(Operand::wire)
+            .collect::<Result<Vec<_>>>()?;
+        if inputs.is_empty() {
+            Err(Error::EmptyConcatenation)
+        } else {
+            Ok(Self::Concat { inputs })
+        }
+    }
+
+    // Checks the constant operands that no circuit width can make valid:
+    // a null divisor, a shift amount of at least 64 bits or an invalid bit range
+    fn validated(self) -> Result<Self> {
+        match &self {
+            Gate::Div { input1, input2 } | Gate::Mod { input1, input2 }
+                if *input2 == Operand::Value(0) =>
+            {
+                Err(Error::DivisionByZero(input1.to_string()))
+            }
+            Gate::LShift {
+                shift: Operand::Value(shift),
+                ..
+            }
+            | Gate::RShift {
+                shift: Operand::Value(shift),
+                ..
+            }
+            | Gate::RotL {
+                shift: Operand::Value(shift),
+                ..
+            }
+            | Gate::RotR {
+                shift: Operand::Value(shift),
+                ..
+            } if *shift >= u64::from(Width::MAX) => Err(Error::TooLargeShift(
+                u8::try_from(*shift).unwrap_or(u8::MAX),
+            )),
+            Gate::Slice { msb, lsb, .. } if lsb > msb || *msb >= Width::MAX => {
+                Err(Error::InvalidBitRange(*msb, *lsb))
+            }
+            _ => Ok(self),
+        }
+    }
+
+    /// Returns the operands of the gate, in order.
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Gate::And { input1, input2 }
+            | Gate::Or { input1, input2 }
+            | Gate::Xor { input1, input2 }
+            | Gate::Nand { input1, input2 }
+            | Gate::Nor { input1, input2 }
+            | Gate::Xnor { input1, input2 }
+            | Gate::Add { input1, input2 }
+            | Gate::Sub { input1, input2 }
+            | Gate::Mul { input1, input2 }
+            | Gate::Div { input1, input2 }
+            | Gate::Mod { input1, input2 }
+            | Gate::Eq { input1, input2 }
+            | Gate::Ne { input1, input2 }
+            | Gate::Lt { input1, input2 }
+            | Gate::Le { input1, input2 }
+            | Gate::Gt { input1, input2 }
+            | Gate::Ge { input1, input2 } => {
+                vec![input1, input2]
+            }
+            Gate::LShift { input, shift }
+            | Gate::RShift { input, shift }
+            | Gate::RotL { input, shift }
+            | Gate::RotR { input, shift } => vec![input, shift],
+            Gate::Not { input } | Gate::Slice { input, .. } => vec![input],
+            Gate::Mux {
+                select,
+                input1,
+                input2,
+            } => vec![select, input1, input2],
+            Gate::Concat { inputs } => inputs.iter().collect(),
+        }
+    }
+
+    /// The kinds of gates, as returned by [`kind()`](Self::kind).
+    pub const KINDS: [&'static str; 25] = [
+        "AND", "OR", "XOR", "NAND", "NOR", "XNOR", "ADD", "SUB", "MUL", "DIV", "MOD", "EQ", "NE",
+        "LT", "LE", "GT", "GE", "LSHIFT", "RSHIFT", "ROTL", "ROTR", "NOT", "MUX", "SLICE",
+        "CONCAT",
+    ];
+
+    /// Returns the kind of the gate: its keyword, or SLICE and CONCAT for bit ranges
+    /// and concatenations.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Gate::And { .. } => "AND",
+            Gate::Or { .. } => "OR",
+            Gate::Xor { .. } => "XOR",
+            Gate::Nand { .. } => "NAND",
+            Gate::Nor { .. } => "NOR",
+            Gate::Xnor { .. } => "XNOR",
+            Gate::Add { .. } => "ADD",
+            Gate::Sub { .. } => "SUB",
+            Gate::Mul { .. } => "MUL",
+            Gate::Div { .. } => "DIV",
+            Gate::Mod { .. } => "MOD",
+            Gate::Eq { .. } => "EQ",
+            Gate::Ne { .. } => "NE",
+            Gate::Lt { .. } => "LT",
+            Gate::Le { .. } => "LE",
+            Gate::Gt { .. } => "GT",
+            Gate::Ge { .. } => "GE",
+            Gate::LShift { .. } => "LSHIFT",
+            Gate::RShift { .. } => "RSHIFT",
+            Gate::RotL { .. } => "ROTL",
+            Gate::RotR { .. } => "ROTR",
+            Gate::Not { .. } => "NOT",
+            Gate::Mux { .. } => "MUX",
+            Gate::Slice { .. } => "SLICE",
+            Gate::Concat { .. } => "CONCAT",
+        }
+    }
+
+    /// Returns `true` for divisions and modulos, which are uncomputable for a zero divisor.
+    pub fn divides(&self) -> bool {
+        matches!(self, Gate::Div { .. } | Gate::Mod { .. })
+    }
+
+    /// Returns the wires feeding the gate, in order.
+    pub fn inputs(&self) -> Vec<&WireId> {
+        self.operands()
+            .into_iter()
+            .filter_map(Operand::as_wire)
+            .collect()
+    }
+
     pub fn has_input(&self, id: &WireId) -> bool {
+        self.inputs().contains(&id)
+    }
+
+    /// Returns the first input whose width differs from that of the other operands
+    /// of the gate, or from `output` when the gate outputs signals as wide as its operands.  
+    /// Shift amounts and multiplexer selectors may have any width,
+    /// and so may the output of a comparison.  
+    /// A slice must lie within its input and the output of a slice or a concatenation
+    /// must be at least as wide as the bits it gathers.  
+    /// `width_of` returns the width of a wire if it exists.
+    pub fn mismatched_input(
+        &self,
+        output: Width,
+        width_of: &dyn Fn(&WireId) -> Option<Width>,
+    ) -> Option<&WireId> {
+        let (operands, mut expected) = match self {
+            Gate::Eq { input1, input2 }
+            | Gate::Ne { input1, input2 }
+            | Gate::Lt { input1, input2 }
+            | Gate::Le { input1, input2 }
+            | Gate::Gt { input1, input2 }
+            | Gate::Ge { input1, input2 } => (vec![input1, input2], None),
+            Gate::LShift { input, .. }
+            | Gate::RShift { input, .. }
+            | Gate::RotL { input, .. }
+            | Gate::RotR { input, .. } => (vec![input], Some(output)),
+            Gate::Mux { input1, input2, .. } => (vec![input1, input2], Some(output)),
+            Gate::Slice { input, msb, lsb } => {
+                let input = input.as_wire()?;
+                let too_narrow = width_of(input).is_some_and(|w| w.bits() <= *msb);
+                return Some(input).filter(|_| too_narrow || output.bits() <= msb - lsb);
+            }
+            Gate::Concat { inputs } => {
+                let mut bits = 0;
+                return inputs.iter().filter_map(Operand::as_wire).find(|input| {
+                    bits += width_of(input).map_or(0, |w| u32::from(w.bits()));
+                    bits > u32::from(output.bits())
+                });
+            }
+            _ => (self.operands(), Some(output)),
+        };
+        for operand in operands.into_iter().filter_map(Operand::as_wire) {
+            if let Some(width) = width_of(operand) {
+                match expected {
+                    Some(expected) if expected != width => return Some(operand),
+                    Some(_) => {}
+                    None => expected = Some(width),
+                }
+            }
+        }
+        None
+    }
+
+    /// Checks that the values and shift amounts of the gate fit in `width` bits.
+    pub fn check_width(&self, width: Width) -> Result<()> {
+        match self {
+            Gate::LShift { input, shift }
+            | Gate::RShift { input, shift }
+            | Gate::RotL { input, shift }
+            | Gate::RotR { input, shift } => {
+                if let Operand::Value(value) = input {
+                    width.check_value(*value)?;
+                }
+                match shift {
+                    Operand::Value(shift) => width.check_shift(u8::try_from(*shift).unwrap()),
+                    Operand::Wire(_) => Ok(()),
+                }
+            }
+            _ => self
+                .operands()
+                .into_iter()
+                .try_for_each(|operand| match operand {
+                    Operand::Value(value) => width.check_value(*value),
+                    Operand::Wire(_) => Ok(()),
+                }),
+        }
+    }
+
+    // Merges the signals of the input wires of the gate with its constant operands,
+    // converted by `from_value`, into the signals of its operands in order
+    fn operand_signals<T: Copy>(&self, inputs: &[T], from_value: impl Fn(u64) -> T) -> Vec<T> {
+        let mut inputs = inputs.iter();
+        self.operands()
+            .into_iter()
+            .map(|operand| match operand {
+                Operand::Wire(_) => *inputs.next().unwrap(),
+                Operand::Value(value) => from_value(*value),
+            })
+            .collect()
+    }
+
+    /// Computes the output of the gate from the signals of its [inputs](Self::inputs),
+    /// truncated to `width` bits.  
+    /// `input_widths` holds the widths of the inputs, which only matter to concatenations.
+    pub fn signal(&self, inputs: &[u64], input_widths: &[Width], width: Width) -> Signal {
+        let operands = self.operand_signals(inputs, |value| value);
+        self.compute(|i| operands[i], |i| input_widths[i], width)
+    }
+
+    /// Computes the output of the gate, truncated to `width` bits, from the signals
+    /// of its [operands](Self::operands) (constants included) given by index by `operand`.  
+    /// `operand_width` gives the widths of the operands, which only matter to concatenations.  
+    /// Unlike [`signal`](Self::signal), this does not allocate.
+    pub fn compute<F, G>(&self, operand: F, operand_width: G, width: Width) -> Signal
+    where
+        F: Fn(usize) -> u64,
+        G: Fn(usize) -> Width,
+    {
+        let input1 = operand(0);
+        let input2 = || operand(1);
+        let value = match self {
+            Gate::And { .. } => input1 & input2(),
+            Gate::Or { .. } => input1 | input2(),
+            Gate::Xor { .. } => input1 ^ input2(),
+            Gate::Nand { .. } => !(input1 & input2()),
+            Gate::Nor { .. } => !(input1 | input2()),
+            Gate::Xnor { .. } => !(input1 ^ input2()),
+            Gate::Add { .. } => input1.wrapping_add(input2()),
+            Gate::Sub { .. } => input1.wrapping_sub(input2()),
+            Gate::Mul { .. } => input1.wrapping_mul(input2()),
+            Gate::Div { .. } => match input1.checked_div(input2()) {
+                Some(quotient) => quotient,
+                None => return Signal::Uncomputable,
+            },
+            Gate::Mod { .. } => match input1.checked_rem(input2()) {
+                Some(remainder) => remainder,
+                None => return Signal::Uncomputable,
+            },
+            Gate::Eq { .. } => (input1 == input2()) as u64,
+            Gate::Ne { .. } => (input1 != input2()) as u64,
+            Gate::Lt { .. } => (input1 < input2()) as u64,
+            Gate::Le { .. } => (input1 <= input2()) as u64,
+            Gate::Gt { .. } => (input1 > input2()) as u64,
+            Gate::Ge { .. } => (input1 >= input2()) as u64,
+            Gate::LShift { .. } => width.shl(input1, input2()),
+            Gate::RShift { .. } => width.shr(input1, input2()),
+            Gate::RotL { .. } => width.rotl(input1, input2()),
+            Gate::RotR { .. } => width.rotr(input1, input2()),
+            Gate::Not { .. } => !input1,
+            Gate::Mux { .. } => {
+                if input1 != 0 {
+                    input2()
+                } else {
+                    operand(2)
+                }
+            }
+            Gate::Slice { msb, lsb, .. } => {
+                (input1 >> lsb) & Width::new(msb - lsb + 1).unwrap().mask()
+            }
+            Gate::Concat { inputs } => (0..inputs.len()).fold(0u64, |value, i| {
+                value
+                    .checked_shl(u32::from(operand_width(i).bits()))
+                    .unwrap_or(0)
+                    | operand(i)
+            }),
+        };
+        Signal::Value(value & width.mask())
+    }
+
+    /// Lane-wise counterpart of [`compute`](Self::compute), computing the outputs of the gate
+    /// for many evaluations at once: `operands[i][lane]` is the value of operand `i`
+    /// in evaluation `lane` and `output[lane]` gets the output of that evaluation.
+    /// Each operation runs over all lanes in a tight loop rather than lane by lane.  
+    /// Lanes dividing by zero get 0, marking them uncomputable being up to the caller.
+    pub fn compute_lanes(
+        &self,
+        operands: &[&[u64]],
+        operand_widths: &[Width],
+        width: Width,
+        output: &mut [u64],
+    ) {
+        let input1 = operands[0];
+        let input2 = || operands[1];
         match self {
-            Gate::And { input1, input2 } => id == input1 || id == input2,
-            Gate::Or { input1, input2 } => id == input1 || id == input2,
-            Gate::AndValue { input, .. } => id == input,
-            Gate::OrValue { input, .. } => id == input,
-            Gate::LShift { input, .. } => id == input,
-            Gate::RShift { input, .. } => id == input,
-            Gate::Not { input } => id == input,
+            Gate::And { .. } => lanes2(output, input1, input2(), |x, y| x & y),
+            Gate::Or { .. } => lanes2(output, input1, input2(), |x, y| x | y),
+            Gate::Xor { .. } => lanes2(output, input1, input2(), |x, y| x ^ y),
+            Gate::Nand { .. } => lanes2(output, input1, input2(), |x, y| !(x & y)),
+            Gate::Nor { .. } => lanes2(output, input1, input2(), |x, y| !(x | y)),
+            Gate::Xnor { .. } => lanes2(output, input1, input2(), |x, y| !(x ^ y)),
+            Gate::Add { .. } => lanes2(output, input1, input2(), u64::wrapping_add),
+            Gate::Sub { .. } => lanes2(output, input1, input2(), u64::wrapping_sub),
+            Gate::Mul { .. } => lanes2(output, input1, input2(), u64::wrapping_mul),
+            Gate::Div { .. } => lanes2(output, input1, input2(), |x, y| {
+                x.checked_div(y).unwrap_or(0)
+            }),
+            Gate::Mod { .. } => lanes2(output, input1, input2(), |x, y| {
+                x.checked_rem(y).unwrap_or(0)
+            }),
+            Gate::Eq { .. } => lanes2(output, input1, input2(), |x, y| (x == y) as u64),
+            Gate::Ne { .. } => lanes2(output, input1, input2(), |x, y| (x != y) as u64),
+            Gate::Lt { .. } => lanes2(output, input1, input2(), |x, y| (x < y) as u64),
+            Gate::Le { .. } => lanes2(output, input1, input2(), |x, y| (x <= y) as u64),
+            Gate::Gt { .. } => lanes2(output, input1, input2(), |x, y| (x > y) as u64),
+            Gate::Ge { .. } => lanes2(output, input1, input2(), |x, y| (x >= y) as u64),
+            Gate::LShift { .. } => lanes2(output, input1, input2(), |x, y| width.shl(x, y)),
+            Gate::RShift { .. } => lanes2(output, input1, input2(), |x, y| width.shr(x, y)),
+            Gate::RotL { .. } => lanes2(output, input1, input2(), |x, y| width.rotl(x, y)),
+            Gate::RotR { .. } => lanes2(output, input1, input2(), |x, y| width.rotr(x, y)),
+            Gate::Not { .. } => lanes1(output, input1, |x| !x),
+            Gate::Mux { .. } => {
+                let lanes = output.iter_mut().zip(input1).zip(input2()).zip(operands[2]);
+                for (((output, select), x), y) in lanes {
+                    *output = if *select != 0 { *x } else { *y };
+                }
+            }
+            Gate::Slice { msb, lsb, .. } => {
+                let mask = Width::new(msb - lsb + 1).unwrap().mask();
+                lanes1(output, input1, |x| (x >> lsb) & mask)
+            }
+            Gate::Concat { .. } => {
+                output.fill(0);
+                for (input, input_width) in operands.iter().zip(operand_widths) {
+                    let bits = u32::from(input_width.bits());
+                    for (output, x) in output.iter_mut().zip(*input) {
+                        *output = output.checked_shl(bits).unwrap_or(0) | x;
+                    }
+                }
+            }
+        }
+        let mask = width.mask();
+        for output in output {
+            *output &= mask;
         }
     }
 
-    pub fn signal(&self, input1: u16, input2: Option<u16>) -> Signal {
+    /// Four-state counterpart of [`signal`](Self::signal).
+    /// Gates whose inputs are all known compute the same value as [`signal`](Self::signal),
+    /// a division by zero yielding unknown bits.
+    /// Otherwise high-impedance input bits are treated as unknown and each output bit
+    /// is only unknown if it actually depends on an unknown input bit,
+    /// e.g. `x AND 0` is 0 and the bits of `x ADD 1` below the lowest unknown bit of `x` are known.
+    pub fn four_state_signal(&self, inputs: &[FourState], width: Width) -> FourState {
+        if let Some(values) = inputs
+            .iter()
+            .map(FourState::value)
+            .collect::<Option<Vec<_>>>()
+        {
+            let widths: Vec<Width> = inputs.iter().map(FourState::bit_width).collect();
+            return match self.signal(&values, &widths, width) {
+                Signal::Value(value) => FourState::known(value, width),
+                _ => FourState::unknown(width),
+            };
+        }
+        let max = Width::new(Width::MAX).unwrap();
+        let operands: Vec<FourState> = self
+            .operand_signals(inputs, |value| FourState::known(value, max))
+            .iter()
+            .map(FourState::z_as_x)
+            .collect();
+        let input1 = operands[0].resize(width);
+        let input2 = || operands[1].resize(width);
+        let shift = |f: fn(Width, u64, u64) -> u64| match operands[1].value() {
+            Some(shift) => input1.map_bits(width, |bits| f(width, bits, shift)),
+            None => FourState::unknown(width),
+        };
         match self {
-            Gate::And { .. } => Signal::Value(input1 & input2.unwrap()),
-            Gate::Or { .. } => Signal::Value(input1 | input2.unwrap()),
-            Gate::AndValue { value, .. } => Signal::Value(input1 & value),
-            Gate::OrValue { value, .. } => Signal::Value(input1 | value),
-            Gate::LShift { shift, .. } => Signal::Value(input1 << shift),
-            Gate::RShift { shift, .. } => Signal::Value(input1 >> shift),
-            Gate::Not { .. } => Signal::Value(!input1),
+            Gate::And { .. } => input1.and(&input2()),
+            Gate::Or { .. } => input1.or(&input2()),
+            Gate::Xor { .. } => input1.xor(&input2()),
+            Gate::Nand { .. } => input1.and(&input2()).not(),
+            Gate::Nor { .. } => input1.or(&input2()).not(),
+            Gate::Xnor { .. } => input1.xor(&input2()).not(),
+            Gate::Add { .. } => input1.below_undefined(&input2(), u64::wrapping_add),
+            Gate::Sub { .. } => input1.below_undefined(&input2(), u64::wrapping_sub),
+            Gate::Mul { .. } => input1.below_undefined(&input2(), u64::wrapping_mul),
+            Gate::Eq { .. } => operands[0].equals(&operands[1], width),
+            Gate::Ne { .. } => operands[0]
+                .equals(&operands[1], width)
+                .xor(&FourState::known(1, width)),
+            Gate::Lt { .. } => operands[0].less_than(&operands[1], width),
+            Gate::Gt { .. } => operands[1].less_than(&operands[0], width),
+            Gate::Le { .. } => operands[1]
+                .less_than(&operands[0], width)
+                .xor(&FourState::known(1, width)),
+            Gate::Ge { .. } => operands[0]
+                .less_than(&operands[1], width)
+                .xor(&FourState::known(1, width)),
+            Gate::Div { .. } | Gate::Mod { .. } => FourState::unknown(width),
+            Gate::LShift { .. } => shift(Width::shl),
+            Gate::RShift { .. } => shift(Width::shr),
+            Gate::RotL { .. } => shift(Width::rotl),
+            Gate::RotR { .. } => shift(Width::rotr),
+            Gate::Not { .. } => input1.not(),
+            Gate::Mux { .. } => {
+                let known = FourState::known(0, width);
+                let (input1, input2) = (input2().or(&known), operands[2].resize(width).or(&known));
+                // A single known 1 selects input1, whatever the undefined bits are
+                if operands[0].ones() != 0 {
+                    input1
+                } else if operands[0].value().is_some() {
+                    input2
+                } else {
+                    input1.merge(&input2)
+                }
+            }
+            Gate::Slice { msb, lsb, .. } => {
+                let mask = Width::new(msb - lsb + 1).unwrap().mask();
+                operands[0].map_bits(width, |bits| (bits >> lsb) & mask)
+            }
+            Gate::Concat { .. } => operands[1..]
+                .iter()
+                .fold(operands[0], |value, input| value.concat(input, max))
+                .resize(width),
+        }
+    }
+
+    // Helper function of try_from() for slices "input[msb:lsb]" or "input[bit]"
+    // Returns None if the bit range cannot be parsed
+    fn parse_slice(input: &str, range: &str) -> Option<Result<Gate>> {
+        let (msb, lsb) = range.split_once(':').unwrap_or((range, range));
+        let (msb, lsb) = (msb.parse().ok()?, lsb.parse().ok()?);
+        Some(Operand::parse(input).and_then(|input| Gate::Slice { input, msb, lsb }.validated()))
+    }
+
+    // Helper function of try_from() for shift and rotate gates:
+    // an ascii lowercase amount is a wire, anything else must be a u8 value
+    fn parse_shift(
+        input: &str,
+        shift: &str,
+        from_operands: fn(Operand, Operand) -> Gate,
+    ) -> Result<Gate> {
+        let input = Operand::parse(input)?;
+        let shift = if !shift.is_empty() && shift.bytes().all(|b| b.is_ascii_lowercase()) {
+            Operand::wire(shift)?
+        } else {
+            Operand::Value(u64::from(shift.parse::<u8>()?))
+        };
+        from_operands(input, shift).validated()
+    }
+
+    // Helper function of try_from() for gates combining two operands, wires or values.
+    // A value on the left of a wire is moved to the right if the gate is commutative
+    // or has a mirror (e.g. `1 LT x` becomes `x GT 1`), otherwise it stays in place.
+    fn parse_binary(
+        left: &str,
+        right: &str,
+        from_operands: GateFromOperands,
+        swap: Swap,
+    ) -> Result<Gate> {
+        let (left, right) = (Operand::parse(left)?, Operand::parse(right)?);
+        if left.is_value() && !right.is_value() {
+            match swap {
+                Swap::Commutative => return from_operands(right, left).validated(),
+                Swap::Mirror(mirrored) => return mirrored(right, left).validated(),
+                Swap::None => {}
+            }
         }
+        from_operands(left, right).validated()
+    }
+}
+
+type GateFromOperands = fn(Operand, Operand) -> Gate;
+
+// How a binary gate is built when its operands are swapped
+#[derive(Clone, Copy)]
+enum Swap {
+    Commutative,
+    Mirror(GateFromOperands),
+    None,
+}
+
+// Binary gate keywords with the constructors of their gates
+const BINARY_GATES: [(&str, GateFromOperands, Swap); 17] = [
+    (
+        "AND",
+        |input1, input2| Gate::And { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "OR",
+        |input1, input2| Gate::Or { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "XOR",
+        |input1, input2| Gate::Xor { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "NAND",
+        |input1, input2| Gate::Nand { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "NOR",
+        |input1, input2| Gate::Nor { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "XNOR",
+        |input1, input2| Gate::Xnor { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "ADD",
+        |input1, input2| Gate::Add { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "SUB",
+        |input1, input2| Gate::Sub { input1, input2 },
+        Swap::None,
+    ),
+    (
+        "MUL",
+        |input1, input2| Gate::Mul { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "DIV",
+        |input1, input2| Gate::Div { input1, input2 },
+        Swap::None,
+    ),
+    (
+        "MOD",
+        |input1, input2| Gate::Mod { input1, input2 },
+        Swap::None,
+    ),
+    (
+        "EQ",
+        |input1, input2| Gate::Eq { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "NE",
+        |input1, input2| Gate::Ne { input1, input2 },
+        Swap::Commutative,
+    ),
+    (
+        "LT",
+        |input1, input2| Gate::Lt { input1, input2 },
+        Swap::Mirror(|input1, input2| Gate::Gt { input1, input2 }),
+    ),
+    (
+        "LE",
+        |input1, input2| Gate::Le { input1, input2 },
+        Swap::Mirror(|input1, input2| Gate::Ge { input1, input2 }),
+    ),
+    (
+        "GT",
+        |input1, input2| Gate::Gt { input1, input2 },
+        Swap::Mirror(|input1, input2| Gate::Lt { input1, input2 }),
+    ),
+    (
+        "GE",
+        |input1, input2| Gate::Ge { input1, input2 },
+        Swap::Mirror(|input1, input2| Gate::Le { input1, input2 }),
+    ),
+];
+
+// Shift and rotate gate keywords with the constructors of their gates
+const SHIFT_GATES: [(&str, GateFromOperands); 4] = [
+    ("LSHIFT", |input, shift| Gate::LShift { input, shift }),
+    ("RSHIFT", |input, shift| Gate::RShift { input, shift }),
+    ("ROTL", |input, shift| Gate::RotL { input, shift }),
+    ("ROTR", |input, shift| Gate::RotR { input, shift }),
+];
+
+// Sets each lane of `output` to `f` of the same lane of `input`
+fn lanes1<F: Fn(u64) -> u64>(output: &mut [u64], input: &[u64], f: F) {
+    for (output, x) in output.iter_mut().zip(input) {
+        *output = f(*x);
+    }
+}
+
+// Sets each lane of `output` to `f` of the same lanes of `input1` and `input2`
+fn lanes2<F: Fn(u64, u64) -> u64>(output: &mut [u64], input1: &[u64], input2: &[u64], f: F) {
+    for ((output, x), y) in output.iter_mut().zip(input1).zip(input2) {
+        *output = f(*x, *y);
     }
 }
 
@@ -96,38 +1049,52 @@ impl TryFrom<&str> for Gate {
     type Error = Error;
 
     fn try_from(s: &str) -> Result<Self> {
+        if let Some(inputs) = s.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            if inputs.is_empty() {
+                return Err(Error::EmptyConcatenation);
+            }
+            return Gate::concat(inputs.split(", "));
+        }
+        if let Some((input, range)) = s.strip_suffix(']').and_then(|s| s.split_once('[')) {
+            return Gate::parse_slice(input, range).ok_or(Error::ParseGate(s.to_string()))?;
+        }
         let elements: Vec<&str> = s.split(' ').collect();
         match elements.len() {
             2 => {
                 if elements[0] == "NOT" {
-                    Ok(Gate::not(elements[1])?)
+                    Ok(Gate::Not {
+                        input: Operand::parse(elements[1])?,
+                    })
                 } else {
                     Err(Error::ParseGate(s.to_string()))
                 }
             }
-            3 => match elements[1] {
-                "AND" => {
-                    if let Ok(value) = elements[0].parse::<u16>() {
-                        Gate::and_value(elements[2], value)
-                    } else if let Ok(value) = elements[2].parse::<u16>() {
-                        Gate::and_value(elements[0], value)
-                    } else {
-                        Gate::and(elements[0], elements[2])
-                    }
+            3 => {
+                let (left, keyword, right) = (elements[0], elements[1], elements[2]);
+                if let Some((_, from_operands, swap)) = BINARY_GATES
+                    .iter()
+                    .find(|(binary, _, _)| *binary == keyword)
+                {
+                    Gate::parse_binary(left, right, *from_operands, *swap)
+                } else if let Some((_, from_operands)) =
+                    SHIFT_GATES.iter().find(|(shift, _)| *shift == keyword)
+                {
+                    Gate::parse_shift(left, right, *from_operands)
+                } else {
+                    Err(Error::ParseGate(s.to_string()))
                 }
-                "OR" => {
-                    if let Ok(value) = elements[0].parse::<u16>() {
-                        Gate::or_value(elements[2], value)
-                    } else if let Ok(value) = elements[2].parse::<u16>() {
-                        Gate::or_value(elements[0], value)
-                    } else {
-                        Gate::or(elements[0], elements[2])
-                    }
+            }
+            4 => {
+                if elements[1] == "MUX" {
+                    Ok(Gate::Mux {
+                        select: Operand::parse(elements[0])?,
+                        input1: Operand::parse(elements[2])?,
+                        input2: Operand::parse(elements[3])?,
+                    })
+                } else {
+                    Err(Error::ParseGate(s.to_string()))
                 }
-                "LSHIFT" => Gate::lshift(elements[0], elements[2].parse::<u8>()?),
-                "RSHIFT" => Gate::rshift(elements[0], elements[2].parse::<u8>()?),
-                _ => Err(Error::ParseGate(s.to_string())),
-            },
+            }
             _ => Err(Error::ParseGate(s.to_string())),
         }
     }
@@ -139,14 +1106,53 @@ impl Display for Gate {
             Gate::And { input1, input2 } => {
                 write!(f, "{} AND {}", input1, input2)
             }
-            Gate::AndValue { input, value } => {
-                write!(f, "{} AND {}", input, value)
-            }
             Gate::Or { input1, input2 } => {
                 write!(f, "{} OR {}", input1, input2)
             }
-            Gate::OrValue { input, value } => {
-                write!(f, "{} OR {}", input, value)
+            Gate::Xor { input1, input2 } => {
+                write!(f, "{} XOR {}", input1, input2)
+            }
+            Gate::Nand { input1, input2 } => {
+                write!(f, "{} NAND {}", input1, input2)
+            }
+            Gate::Nor { input1, input2 } => {
+                write!(f, "{} NOR {}", input1, input2)
+            }
+            Gate::Xnor { input1, input2 } => {
+                write!(f, "{} XNOR {}", input1, input2)
+            }
+            Gate::Add { input1, input2 } => {
+                write!(f, "{} ADD {}", input1, input2)
+            }
+            Gate::Sub { input1, input2 } => {
+                write!(f, "{} SUB {}", input1, input2)
+            }
+            Gate::Mul { input1, input2 } => {
+                write!(f, "{} MUL {}", input1, input2)
+            }
+            Gate::Div { input1, input2 } => {
+                write!(f, "{} DIV {}", input1, input2)
+            }
+            Gate::Mod { input1, input2 } => {
+                write!(f, "{} MOD {}", input1, input2)
+            }
+            Gate::Eq { input1, input2 } => {
+                write!(f, "{} EQ {}", input1, input2)
+            }
+            Gate::Ne { input1, input2 } => {
+                write!(f, "{} NE {}", input1, input2)
+            }
+            Gate::Lt { input1, input2 } => {
+                write!(f, "{} LT {}", input1, input2)
+            }
+            Gate::Le { input1, input2 } => {
+                write!(f, "{} LE {}", input1, input2)
+            }
+            Gate::Gt { input1, input2 } => {
+                write!(f, "{} GT {}", input1, input2)
+            }
+            Gate::Ge { input1, input2 } => {
+                write!(f, "{} GE {}", input1, input2)
             }
             Gate::LShift { input, shift } => {
                 write!(f, "{} LSHIFT {}", input, shift)
@@ -154,9 +1160,33 @@ impl Display for Gate {
             Gate::RShift { input, shift } => {
                 write!(f, "{} RSHIFT {}", input, shift)
             }
+            Gate::RotL { input, shift } => {
+                write!(f, "{} ROTL {}", input, shift)
+            }
+            Gate::RotR { input, shift } => {
+                write!(f, "{} ROTR {}", input, shift)
+            }
             Gate::Not { input } => {
                 write!(f, "NOT {}", input)
             }
+            Gate::Mux {
+                select,
+                input1,
+                input2,
+            } => {
+                write!(f, "{} MUX {} {}", select, input1, input2)
+            }
+            Gate::Slice { input, msb, lsb } => {
+                if msb == lsb {
+                    write!(f, "{}[{}]", input, msb)
+                } else {
+                    write!(f, "{}[{}:{}]", input, msb, lsb)
+                }
+            }
+            Gate::Concat { inputs } => {
+                let inputs: Vec<String> = inputs.iter().map(Operand::to_string).collect();
+                write!(f, "{{{}}}", inputs.join(", "))
+            }
         }
     }
 }
@@ -185,18 +1215,49 @@ mod tests {
     #[test]
     fn shift_amount() {
         assert!(Gate::lshift("sh", 0).is_ok());
-        assert!(Gate::rshift("sh", 15).is_ok());
+        assert!(Gate::rshift("sh", 63).is_ok());
+
+        assert!(matches!(
+            Gate::rshift("sh", 64),
+            Err(Error::TooLargeShift(64))
+        ));
+        assert!(matches!(
+            Gate::try_from("a LSHIFT 71"),
+            Err(Error::TooLargeShift(71))
+        ));
+        assert!(Gate::rotr("sh", 63).is_ok());
+        assert!(matches!(
+            Gate::rotl("sh", 64),
+            Err(Error::TooLargeShift(64))
+        ));
 
+        let w = Width::default();
+        assert!(Gate::rshift("sh", 15).unwrap().check_width(w).is_ok());
         assert!(matches!(
-            Gate::rshift("sh", 16),
+            Gate::rshift("sh", 16).unwrap().check_width(w),
             Err(Error::TooLargeShift(16))
         ));
         assert!(matches!(
-            Gate::try_from("a LSHIFT 31"),
+            Gate::rotl("sh", 31).unwrap().check_width(w),
             Err(Error::TooLargeShift(31))
         ));
     }
 
+    #[test]
+    fn division_by_zero() {
+        assert!(Gate::div("x", "y").is_ok());
+        assert!(Gate::mod_value("x", 1).is_ok());
+
+        assert!(matches!(
+            Gate::div_value("x", 0),
+            Err(Error::DivisionByZero(_))
+        ));
+        assert!(matches!(
+            Gate::try_from("x MOD 0"),
+            Err(Error::DivisionByZero(_))
+        ));
+    }
+
     #[test]
     fn parse_gate() {
         assert!(matches!(Gate::try_from(""), Err(Error::ParseGate(_))));
@@ -216,9 +1277,243 @@ mod tests {
     #[test]
     fn parse_shift() {
         assert!(matches!(
-            Gate::try_from("a RSHIFT a"),
+            Gate::try_from("a RSHIFT 1a"),
             Err(Error::ParseShift(_))
         ));
+        assert!(matches!(
+            Gate::try_from("a ROTL B"),
+            Err(Error::ParseShift(_))
+        ));
+    }
+
+    #[test]
+    fn four_state_signal() -> Result<()> {
+        let w = Width::new(4)?;
+        let x = FourState::unknown(w);
+        let z = FourState::high_impedance(w);
+        let known = |value| FourState::known(value, w);
+
+        assert_eq!(
+            Gate::and("x", "y")?.four_state_signal(&[x, known(0)], w),
+            known(0)
+        );
+        assert_eq!(
+            Gate::or_value("x", 0xf)?.four_state_signal(&[x], w),
+            known(0xf)
+        );
+        assert_eq!(
+            Gate::nand("x", "y")?.four_state_signal(&[z, known(0)], w),
+            known(0xf)
+        );
+        assert_eq!(Gate::xor("x", "y")?.four_state_signal(&[x, known(0)], w), x);
+        assert_eq!(Gate::not("x")?.four_state_signal(&[z], w), x);
+        assert_eq!(
+            Gate::and_value("x", 0b0110)?
+                .four_state_signal(&[x], w)
+                .to_string(),
+            "0xx0"
+        );
+        assert_eq!(
+            Gate::lshift("x", 2)?.four_state_signal(&[z], w).to_string(),
+            "xx00"
+        );
+        assert_eq!(
+            Gate::slice("x", 2, 1)?
+                .four_state_signal(&[z], Width::new(2)?)
+                .to_string(),
+            "xx"
+        );
+        assert_eq!(
+            Gate::lshift_wire("x", "y")?.four_state_signal(&[known(1), x], w),
+            x
+        );
+        assert_eq!(
+            Gate::mux("s", "x", "y")?
+                .four_state_signal(&[x, known(0b0101), known(0b0111)], w)
+                .to_string(),
+            "01x1"
+        );
+        assert_eq!(
+            Gate::mux("s", "x", "y")?.four_state_signal(&[known(0), x, known(3)], w),
+            known(3)
+        );
+        let partly_x = Gate::or_value("x", 0b0100)?.four_state_signal(&[x], w);
+        assert_eq!(
+            Gate::mux("s", "x", "y")?.four_state_signal(&[partly_x, known(5), x], w),
+            known(5)
+        );
+        let partly_x = Gate::and_value("x", 0b0100)?.four_state_signal(&[x], w);
+        assert_eq!(
+            Gate::mux("s", "x", "y")?
+                .four_state_signal(&[partly_x, known(0b0101), known(0b0111)], w)
+                .to_string(),
+            "01x1"
+        );
+        assert_eq!(
+            Gate::eq_value("x", 0)?
+                .four_state_signal(&[Gate::or_value("x", 1)?.four_state_signal(&[x], w)], w),
+            known(0)
+        );
+        assert_eq!(
+            Gate::lt("x", "y")?
+                .four_state_signal(&[x, known(1)], w)
+                .to_string(),
+            "000x"
+        );
+        // Known high bits decide comparisons despite unknown low bits
+        let high = Gate::or_value("x", 0b1000)?.four_state_signal(&[x], w);
+        assert_eq!(
+            Gate::gt_value("x", 7)?.four_state_signal(&[high], w),
+            known(1)
+        );
+        assert_eq!(
+            Gate::le_value("x", 7)?.four_state_signal(&[high], w),
+            known(0)
+        );
+        assert_eq!(
+            Gate::lt_value("x", 8)?.four_state_signal(&[high], w),
+            known(0)
+        );
+        assert_eq!(
+            Gate::ge_value("x", 8)?.four_state_signal(&[high], w),
+            known(1)
+        );
+        assert_eq!(
+            Gate::ge_value("x", 9)?
+                .four_state_signal(&[high], w)
+                .to_string(),
+            "000x"
+        );
+        assert_eq!(
+            Gate::lt("x", "y")?.four_state_signal(&[x, known(0)], w),
+            known(0)
+        );
+        assert_eq!(
+            Gate::div("x", "y")?.four_state_signal(&[known(1), known(0)], w),
+            x
+        );
+        assert_eq!(
+            Gate::add("x", "y")?.four_state_signal(&[known(1), known(2)], w),
+            known(3)
+        );
+        assert_eq!(
+            Gate::concat(["x", "y"])?
+                .four_state_signal(
+                    &[
+                        FourState::unknown(Width::new(2)?),
+                        FourState::known(1, Width::new(2)?)
+                    ],
+                    w
+                )
+                .to_string(),
+            "xx01"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn try_from() -> Result<()> {
+        assert_eq!(Gate::try_from("a XOR b")?, Gate::xor("a", "b")?);
+        assert_eq!(Gate::try_from("a NAND 3")?, Gate::nand_value("a", 3)?);
+        assert_eq!(Gate::try_from("3 NOR a")?, Gate::nor_value("a", 3)?);
+        assert_eq!(Gate::try_from("a XNOR b")?, Gate::xnor("a", "b")?);
+        assert!(matches!(Gate::try_from("a XOR"), Err(Error::ParseGate(_))));
+        assert_eq!(Gate::try_from("a ADD 1")?, Gate::add_value("a", 1)?);
+        assert_eq!(Gate::try_from("1 MUL a")?, Gate::mul_value("a", 1)?);
+        assert_eq!(Gate::try_from("a SUB 1")?, Gate::sub_value("a", 1)?);
+        assert_eq!(Gate::try_from("a DIV b")?, Gate::div("a", "b")?);
+        assert_eq!(Gate::try_from("a MOD 9")?, Gate::mod_value("a", 9)?);
+        assert_eq!(Gate::try_from("a RSHIFT a")?, Gate::rshift_wire("a", "a")?);
+        assert_eq!(Gate::try_from("a LSHIFT b")?, Gate::lshift_wire("a", "b")?);
+        assert_eq!(Gate::try_from("a EQ b")?, Gate::eq("a", "b")?);
+        assert_eq!(Gate::try_from("3 NE a")?, Gate::ne_value("a", 3)?);
+        assert_eq!(Gate::try_from("a LT 3")?, Gate::lt_value("a", 3)?);
+        assert_eq!(Gate::try_from("3 LT a")?, Gate::gt_value("a", 3)?);
+        assert_eq!(Gate::try_from("3 LE a")?, Gate::ge_value("a", 3)?);
+        assert_eq!(Gate::try_from("3 GT a")?, Gate::lt_value("a", 3)?);
+        assert_eq!(Gate::try_from("a GE b")?, Gate::ge("a", "b")?);
+        assert_eq!(Gate::try_from("a[7:4]")?, Gate::slice("a", 7, 4)?);
+        assert_eq!(Gate::try_from("a[3]")?, Gate::slice("a", 3, 3)?);
+        assert_eq!(Gate::try_from("{a, b, c}")?, Gate::concat(["a", "b", "c"])?);
+        assert_eq!(Gate::try_from("{a}")?, Gate::concat(["a"])?);
+        assert!(matches!(Gate::try_from("a[x]"), Err(Error::ParseGate(_))));
+        assert!(matches!(Gate::try_from("a[1:]"), Err(Error::ParseGate(_))));
+        assert!(matches!(
+            Gate::try_from("a[64:0]"),
+            Err(Error::InvalidBitRange(64, 0))
+        ));
+        assert!(matches!(
+            Gate::try_from("{a,b}"),
+            Err(Error::InvalidWireId(_))
+        ));
+        assert!(matches!(
+            Gate::try_from("{}"),
+            Err(Error::EmptyConcatenation)
+        ));
+        assert!(matches!(
+            Gate::concat(Vec::<String>::new()),
+            Err(Error::EmptyConcatenation)
+        ));
+        assert_eq!(Gate::try_from("s MUX a b")?, Gate::mux("s", "a", "b")?);
+        assert_eq!(Gate::try_from("s MUX a 1")?.to_string(), "s MUX a 1");
+        assert!(matches!(
+            Gate::try_from("s MUX a B"),
+            Err(Error::InvalidWireId(_))
+        ));
+        assert!(matches!(
+            Gate::try_from("s AND a b"),
+            Err(Error::ParseGate(_))
+        ));
+        assert_eq!(Gate::try_from("a ROTL 3")?, Gate::rotl("a", 3)?);
+        assert_eq!(Gate::try_from("a ROTR b")?, Gate::rotr_wire("a", "b")?);
+        Ok(())
+    }
+
+    #[test]
+    fn constant_operands() -> Result<()> {
+        let w = Width::new(8)?;
+        for (s, value) in [
+            ("1 AND 3", 1),
+            ("NOT 5", 0xfa),
+            ("5 SUB 7", 0xfe),
+            ("200 ADD 100", 44),
+            ("7 DIV 2", 3),
+            ("3 LT 4", 1),
+            ("1 LSHIFT 3", 8),
+            ("128 ROTL 1", 1),
+            ("0 MUX 1 2", 2),
+            ("12[3:2]", 3),
+        ] {
+            let gate = Gate::try_from(s)?;
+            assert!(gate.inputs().is_empty());
+            assert_eq!(gate.to_string(), s);
+            assert_eq!(gate.signal(&[], &[], w), Signal::Value(value));
+        }
+
+        let gate = Gate::try_from("100 SUB a")?;
+        assert_eq!(gate.to_string(), "100 SUB a");
+        assert_eq!(gate.signal(&[1], &[], w), Signal::Value(99));
+        let gate = Gate::try_from("3 LSHIFT n")?;
+        assert_eq!(gate.inputs(), vec![&WireId::new("n")?]);
+        assert_eq!(gate.signal(&[2], &[], w), Signal::Value(12));
+        let gate = Gate::try_from("s MUX 4 b")?;
+        assert_eq!(gate.inputs(), vec![&WireId::new("s")?, &WireId::new("b")?]);
+        assert_eq!(gate.signal(&[1, 9], &[], w), Signal::Value(4));
+        assert_eq!(gate.signal(&[0, 9], &[], w), Signal::Value(9));
+
+        assert!(matches!(
+            Gate::try_from("1 DIV 0"),
+            Err(Error::DivisionByZero(_))
+        ));
+        assert!(matches!(
+            Gate::try_from("300 AND 1")?.check_width(w),
+            Err(Error::TooLargeValue(300))
+        ));
+        assert!(matches!(
+            Gate::try_from("1 LSHIFT 8")?.check_width(w),
+            Err(Error::TooLargeShift(8))
+        ));
+        Ok(())
     }
 
     #[test]
@@ -230,36 +1525,223 @@ mod tests {
         assert!(!Gate::not("xx")?.has_input(&WireId::new("x")?));
         assert!(!Gate::not("x")?.has_input(&WireId::new("xx")?));
         assert!(!Gate::and("x", "y")?.has_input(&WireId::new("xy")?));
+        assert!(Gate::mux("s", "x", "y")?.has_input(&WireId::new("s")?));
+        assert!(Gate::mux("s", "x", "y")?.has_input(&WireId::new("y")?));
+        assert!(Gate::lshift_wire("x", "n")?.has_input(&WireId::new("n")?));
         Ok(())
     }
 
     #[test]
     fn signal() -> Result<()> {
+        let w = Width::default();
+        assert_eq!(
+            Gate::slice("x", 11, 4)?.signal(&[0xabcd], &[w], w),
+            Signal::Value(0xbc)
+        );
+        assert_eq!(
+            Gate::concat(["x", "y"])?.signal(&[0x1, 0x2], &[Width::new(4)?, Width::new(8)?], w),
+            Signal::Value(0x102)
+        );
+        assert_eq!(
+            Gate::concat(["x", "y"])?.signal(&[0x1, 0x2], &[w, w], w),
+            Signal::Value(0x2)
+        );
+        assert_eq!(
+            Gate::eq("x", "y")?.signal(&[5, 5], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::ne("x", "y")?.signal(&[5, 5], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::lt("x", "y")?.signal(&[4, 5], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::le("x", "y")?.signal(&[6, 5], &[], w),
+            Signal::Value(0)
+        );
         assert_eq!(
-            Gate::and("x", "y")?.signal(353, Some(57)),
+            Gate::gt("x", "y")?.signal(&[6, 5], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::ge("x", "y")?.signal(&[5, 5], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::eq_value("x", 5)?.signal(&[4], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::ne_value("x", 5)?.signal(&[4], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::lt_value("x", 5)?.signal(&[5], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::le_value("x", 5)?.signal(&[5], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::gt_value("x", 5)?.signal(&[4], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::ge_value("x", 5)?.signal(&[4], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::mux("s", "x", "y")?.signal(&[2, 11, 22], &[], w),
+            Signal::Value(11)
+        );
+        assert_eq!(
+            Gate::mux("s", "x", "y")?.signal(&[0, 11, 22], &[], w),
+            Signal::Value(22)
+        );
+        assert_eq!(
+            Gate::and("x", "y")?.signal(&[353, 57], &[], w),
             Signal::Value(353 & 57)
         );
         assert_eq!(
-            Gate::or("x", "y")?.signal(119, Some(3222)),
+            Gate::or("x", "y")?.signal(&[119, 3222], &[], w),
             Signal::Value(119 | 3222)
         );
         assert_eq!(
-            Gate::and_value("x", 226)?.signal(27, None),
+            Gate::and_value("x", 226)?.signal(&[27], &[], w),
             Signal::Value(27 & 226)
         );
         assert_eq!(
-            Gate::or_value("x", 913)?.signal(172, None),
+            Gate::or_value("x", 913)?.signal(&[172], &[], w),
             Signal::Value(172 | 913)
         );
         assert_eq!(
-            Gate::lshift("x", 7)?.signal(34, None),
+            Gate::lshift("x", 7)?.signal(&[34], &[], w),
             Signal::Value(34 << 7)
         );
         assert_eq!(
-            Gate::rshift("x", 3)?.signal(1925, None),
+            Gate::rshift("x", 3)?.signal(&[1925], &[], w),
             Signal::Value(1925 >> 3)
         );
-        assert_eq!(Gate::not("x")?.signal(0xa56e, None), Signal::Value(!0xa56e));
+        assert_eq!(
+            Gate::not("x")?.signal(&[0xa56e], &[], w),
+            Signal::Value(0x5a91)
+        );
+        assert_eq!(
+            Gate::xor("x", "y")?.signal(&[0x0ff0, 0x00ff], &[], w),
+            Signal::Value(0x0f0f)
+        );
+        assert_eq!(
+            Gate::nand("x", "y")?.signal(&[0x0ff0, 0x00ff], &[], w),
+            Signal::Value(0xff0f)
+        );
+        assert_eq!(
+            Gate::nor("x", "y")?.signal(&[0x0ff0, 0x00ff], &[], w),
+            Signal::Value(0xf000)
+        );
+        assert_eq!(
+            Gate::xnor("x", "y")?.signal(&[0x0ff0, 0x00ff], &[], w),
+            Signal::Value(0xf0f0)
+        );
+        assert_eq!(
+            Gate::xor_value("x", 0x00ff)?.signal(&[0x0ff0], &[], w),
+            Signal::Value(0x0f0f)
+        );
+        assert_eq!(
+            Gate::nand_value("x", 0x00ff)?.signal(&[0x0ff0], &[], w),
+            Signal::Value(0xff0f)
+        );
+        assert_eq!(
+            Gate::nor_value("x", 0x00ff)?.signal(&[0x0ff0], &[], w),
+            Signal::Value(0xf000)
+        );
+        assert_eq!(
+            Gate::xnor_value("x", 0x00ff)?.signal(&[0x0ff0], &[], w),
+            Signal::Value(0xf0f0)
+        );
+        assert_eq!(
+            Gate::add("x", "y")?.signal(&[0xfff0, 0x0020], &[], w),
+            Signal::Value(0x0010)
+        );
+        assert_eq!(
+            Gate::sub("x", "y")?.signal(&[3, 5], &[], w),
+            Signal::Value(0xfffe)
+        );
+        assert_eq!(
+            Gate::mul("x", "y")?.signal(&[0x0100, 0x0101], &[], w),
+            Signal::Value(0x0100)
+        );
+        assert_eq!(
+            Gate::div("x", "y")?.signal(&[100, 7], &[], w),
+            Signal::Value(14)
+        );
+        assert_eq!(
+            Gate::r#mod("x", "y")?.signal(&[100, 7], &[], w),
+            Signal::Value(2)
+        );
+        assert_eq!(
+            Gate::div("x", "y")?.signal(&[100, 0], &[], w),
+            Signal::Uncomputable
+        );
+        assert_eq!(
+            Gate::r#mod("x", "y")?.signal(&[100, 0], &[], w),
+            Signal::Uncomputable
+        );
+        assert_eq!(
+            Gate::add_value("x", 1)?.signal(&[0xffff], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::sub_value("x", 1)?.signal(&[0], &[], w),
+            Signal::Value(0xffff)
+        );
+        assert_eq!(
+            Gate::mul_value("x", 3)?.signal(&[7], &[], w),
+            Signal::Value(21)
+        );
+        assert_eq!(
+            Gate::div_value("x", 3)?.signal(&[7], &[], w),
+            Signal::Value(2)
+        );
+        assert_eq!(
+            Gate::mod_value("x", 3)?.signal(&[7], &[], w),
+            Signal::Value(1)
+        );
+        assert_eq!(
+            Gate::rotl("x", 4)?.signal(&[0xa56e], &[], w),
+            Signal::Value(0x56ea)
+        );
+        assert_eq!(
+            Gate::rotr("x", 4)?.signal(&[0xa56e], &[], w),
+            Signal::Value(0xea56)
+        );
+        assert_eq!(
+            Gate::lshift_wire("x", "y")?.signal(&[34, 7], &[], w),
+            Signal::Value(34 << 7)
+        );
+        assert_eq!(
+            Gate::rshift_wire("x", "y")?.signal(&[1925, 3], &[], w),
+            Signal::Value(1925 >> 3)
+        );
+        assert_eq!(
+            Gate::lshift_wire("x", "y")?.signal(&[0xffff, 16], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::rshift_wire("x", "y")?.signal(&[0xffff, 300], &[], w),
+            Signal::Value(0)
+        );
+        assert_eq!(
+            Gate::rotl_wire("x", "y")?.signal(&[0xa56e, 20], &[], w),
+            Signal::Value(0x56ea)
+        );
+        assert_eq!(
+            Gate::rotr_wire("x", "y")?.signal(&[0xa56e, 16], &[], w),
+            Signal::Value(0xa56e)
+        );
         Ok(())
     }
 }
@@ -0,0 +1,61 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::wire_id::WireId;
+use crate::error::Result;
+
+/// Operand of a gate: either a wire or a constant value
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Operand {
+    Wire(WireId),
+    Value(u64),
+}
+
+impl Operand {
+    pub fn wire<S: Into<String>>(id: S) -> Result<Self> {
+        Ok(Self::Wire(WireId::new(id)?))
+    }
+
+    /// Parses a value if `s` is a number and a wire id otherwise.
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.parse::<u64>() {
+            Ok(value) => Ok(Self::Value(value)),
+            Err(_) => Self::wire(s),
+        }
+    }
+
+    /// Returns the wire id of the operand if it is a wire.
+    pub fn as_wire(&self) -> Option<&WireId> {
+        match self {
+            Self::Wire(id) => Some(id),
+            Self::Value(_) => None,
+        }
+    }
+
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wire(id) => write!(f, "{}", id),
+            Self::Value(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn parse() -> Result<()> {
+        assert_eq!(Operand::parse("42")?, Operand::Value(42));
+        assert_eq!(Operand::parse("ab")?, Operand::wire("ab")?);
+        assert!(matches!(Operand::parse("4a"), Err(Error::InvalidWireId(_))));
+        assert!(matches!(Operand::parse("-1"), Err(Error::InvalidWireId(_))));
+        Ok(())
+    }
+}
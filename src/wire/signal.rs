@@ -6,5 +6,5 @@ pub enum Signal {
     #[default]
     Uncomputed,
     Uncomputable,
-    Value(u16),
+    Value(u64),
 }
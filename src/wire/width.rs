@@ -0,0 +1,137 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::error::{Error, Result};
+
+/// Number of bits of a signal
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub(crate) struct Width(u8);
+
+impl Width {
+    /// Largest supported width, that of type [u64]
+    pub const MAX: u8 = 64;
+
+    pub fn new(bits: u8) -> Result<Self> {
+        if 0 < bits && bits <= Self::MAX {
+            Ok(Self(bits))
+        } else {
+            Err(Error::InvalidWidth(bits))
+        }
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the largest value representable with this width.
+    pub fn mask(self) -> u64 {
+        u64::MAX >> (Self::MAX - self.0)
+    }
+
+    pub fn fits(self, value: u64) -> bool {
+        value <= self.mask()
+    }
+
+    pub fn check_value(self, value: u64) -> Result<()> {
+        if self.fits(value) {
+            Ok(())
+        } else {
+            Err(Error::TooLargeValue(value))
+        }
+    }
+
+    pub fn check_shift(self, shift: u8) -> Result<()> {
+        if shift < self.0 {
+            Ok(())
+        } else {
+            Err(Error::TooLargeShift(shift))
+        }
+    }
+
+    /// Shifts `value` left by `shift` bits, all bits being shifted out
+    /// if `shift` is not less than the width.
+    pub fn shl(self, value: u64, shift: u64) -> u64 {
+        if shift < u64::from(self.0) {
+            (value << shift) & self.mask()
+        } else {
+            0
+        }
+    }
+
+    /// Shifts `value` right by `shift` bits, all bits being shifted out
+    /// if `shift` is not less than the width.
+    pub fn shr(self, value: u64, shift: u64) -> u64 {
+        if shift < u64::from(self.0) {
+            value >> shift
+        } else {
+            0
+        }
+    }
+
+    /// Rotates `value` left by `shift` bits modulo the width.
+    pub fn rotl(self, value: u64, shift: u64) -> u64 {
+        let shift = shift % u64::from(self.0);
+        if shift == 0 {
+            value
+        } else {
+            self.shl(value, shift) | (value >> (u64::from(self.0) - shift))
+        }
+    }
+
+    /// Rotates `value` right by `shift` bits modulo the width.
+    pub fn rotr(self, value: u64, shift: u64) -> u64 {
+        let shift = shift % u64::from(self.0);
+        if shift == 0 {
+            value
+        } else {
+            self.rotl(value, u64::from(self.0) - shift)
+        }
+    }
+}
+
+impl Default for Width {
+    fn default() -> Self {
+        Self(16)
+    }
+}
+
+impl Display for Width {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_widths() {
+        assert!(Width::new(1).is_ok());
+        assert!(Width::new(64).is_ok());
+
+        assert!(matches!(Width::new(0), Err(Error::InvalidWidth(0))));
+        assert!(matches!(Width::new(65), Err(Error::InvalidWidth(65))));
+    }
+
+    #[test]
+    fn mask() -> Result<()> {
+        assert_eq!(Width::new(1)?.mask(), 1);
+        assert_eq!(Width::default().mask(), 0xffff);
+        assert_eq!(Width::new(64)?.mask(), u64::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn rotations() -> Result<()> {
+        let width = Width::new(8)?;
+        assert_eq!(width.rotl(0x81, 1), 0x03);
+        assert_eq!(width.rotr(0x81, 1), 0xc0);
+        assert_eq!(width.rotl(0x81, 9), 0x03);
+        assert_eq!(width.rotr(0x81, 8), 0x81);
+
+        let width = Width::new(64)?;
+        assert_eq!(width.rotl(1 << 63, 1), 1);
+        assert_eq!(width.rotr(1, 1), 1 << 63);
+        Ok(())
+    }
+}
@@ -10,6 +10,10 @@ impl WireId {
         Self::try_from(id.into())
     }
 
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
     fn is_valid(id: &str) -> bool {
         !id.is_empty() && id.bytes().all(|b| b.is_ascii_lowercase())
     }
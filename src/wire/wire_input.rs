@@ -2,7 +2,17 @@ use super::{gate::Gate, wire_id::WireId};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub(crate) enum WireInput {
-    Value(u16),
+    Value(u64),
+    /// Unknown value (X), only computable in four-state mode
+    Unknown,
+    /// High-impedance value (Z), only computable in four-state mode
+    HighImpedance,
     Wire(WireId),
     Gate(Gate),
+    /// Register (D flip-flop) latching the signal of wire `input` on each clock cycle,
+    /// starting from value `initial`
+    Register {
+        input: WireId,
+        initial: u64,
+    },
 }